@@ -0,0 +1,453 @@
+//! Attack sets and square-relation tables.
+//!
+//! Knight, king and pawn attacks and the between/line tables are computed by
+//! `const fn`s at compile time. Bishop and rook attacks are looked up in
+//! tables indexed with magic bitboards; with the `pext` feature on an x86_64
+//! CPU that supports BMI2 they are indexed with the `pext` instruction
+//! instead. Which indexing is used is only known at runtime, so those tables
+//! are built once, on first use, and shared by all positions and threads.
+//!
+//! The magics below were found with a seeded random search over sparse
+//! candidates. They depend only on the raw bit index of each square.
+//!
+//! The tables work on raw `u64`s indexed by [`Square::index`]; the public
+//! functions wrap them in [`Square`] and [`Bitboard`].
+
+use crate::{Bitboard, Color, Square};
+use std::sync::OnceLock;
+
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const KNIGHT_STEPS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const WHITE_PAWN_STEPS: [(i32, i32); 2] = [(1, 1), (-1, 1)];
+const BLACK_PAWN_STEPS: [(i32, i32); 2] = [(1, -1), (-1, -1)];
+
+static KNIGHT_ATTACKS: [u64; 64] = leaper_table(&KNIGHT_STEPS);
+static KING_ATTACKS: [u64; 64] = leaper_table(&KING_STEPS);
+static PAWN_ATTACKS: [[u64; 64]; 2] = [
+    leaper_table(&WHITE_PAWN_STEPS),
+    leaper_table(&BLACK_PAWN_STEPS),
+];
+static BETWEEN: [[u64; 64]; 64] = between_table();
+static LINE: [[u64; 64]; 64] = line_table();
+
+const BISHOP_MAGICS: [u64; 64] = [
+    0x10102002004a1420,
+    0x3009080104082090,
+    0x20a2020400200808,
+    0x0204404080020102,
+    0x0101104000000028,
+    0x28811008040000e8,
+    0x1031011032200020,
+    0x0041040118921000,
+    0x0400041004812400,
+    0x4100108188008081,
+    0x0020484604042a09,
+    0x000002208a002100,
+    0x00000a1210002805,
+    0x400a410460448100,
+    0x013060480a086000,
+    0x2101411400840412,
+    0x1a10100404500409,
+    0x4010028401026400,
+    0x2050000800401020,
+    0x0008202404001420,
+    0x0032880400a00600,
+    0x0202000022100202,
+    0x0204082082111040,
+    0x480c210084010800,
+    0x00c2620410200200,
+    0x80c2102042901202,
+    0x9000320050040040,
+    0x8004080010220040,
+    0x0020044002003004,
+    0x120401884100a003,
+    0x2004208014020128,
+    0x04010302005400a0,
+    0x0950084500600402,
+    0x81e0900901102200,
+    0x10040128008412c0,
+    0x0402004042940100,
+    0x2104204010040100,
+    0x0420009100802400,
+    0x0204082220808082,
+    0x2002004248020218,
+    0x0001042160208400,
+    0x00440d0148101080,
+    0x8044a02030000802,
+    0xc081044206204800,
+    0x0000219020800400,
+    0x8404010041000201,
+    0x02210c0102492209,
+    0x8010012110283100,
+    0x0183880109a00001,
+    0x1001411090900080,
+    0x2002120084045420,
+    0x2126087842020022,
+    0x8040004010410128,
+    0x08024030c2008020,
+    0x0121241004812002,
+    0x0308010822004000,
+    0x0083042805141020,
+    0x0220804212102288,
+    0x8000014100880400,
+    0x1000080000840410,
+    0x0088080031203200,
+    0x001002200202c202,
+    0x0000054802540400,
+    0xa010041108003100,
+];
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080004008801020,
+    0x0840092002c03000,
+    0x1900200010400900,
+    0x0880100008000480,
+    0x4200100420080200,
+    0x8100020100080400,
+    0x0200040110886200,
+    0x0200008040220411,
+    0x0404800084400220,
+    0x0000401000402000,
+    0x0086001081220440,
+    0x0408800800100280,
+    0x000a001201040820,
+    0x8848800200840080,
+    0x4001000100040200,
+    0x0442000102105084,
+    0x9080010020804100,
+    0x0040404000201009,
+    0x0000808010002009,
+    0x2200090021d00100,
+    0x0008008008040080,
+    0x0004004002010040,
+    0x0011040008015042,
+    0x00000a0001768104,
+    0x0000800080204009,
+    0x2010004140002001,
+    0x9800200280100080,
+    0x1000100080080080,
+    0x0050500500080100,
+    0x0000020080040080,
+    0x0c10010400420810,
+    0x1040008200005104,
+    0x01808240088004a0,
+    0x0882804004802000,
+    0x0880402001001100,
+    0x0000100080800800,
+    0x2000480131001500,
+    0x0002000400800280,
+    0x0080020104000810,
+    0x80441044120000a1,
+    0x0000800040008020,
+    0x041040201000c000,
+    0x0001004020010010,
+    0x0800100100090021,
+    0x0004080004008080,
+    0x0010040002008080,
+    0x2012004881020004,
+    0x8300842444820011,
+    0x0088403882010200,
+    0x0820400080210100,
+    0x0110910040a00300,
+    0x0801100280080480,
+    0x0242009008200600,
+    0x1002000489500200,
+    0x0040800200010080,
+    0x0091800041000080,
+    0x000c91800020c101,
+    0x0a41104009802103,
+    0x000880401202210a,
+    0x0000300089142101,
+    0x8002002004100802,
+    0x30010002084c0007,
+    0x0888221800813004,
+    0x000008208044010a,
+];
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    Bitboard(KNIGHT_ATTACKS[square.index()])
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    Bitboard(KING_ATTACKS[square.index()])
+}
+
+/// The squares a pawn of the given color on `square` captures on.
+pub fn pawn_attacks(square: Square, color: Color) -> Bitboard {
+    Bitboard(PAWN_ATTACKS[color.index()][square.index()])
+}
+
+/// Everything a slider on the board can hit from `square`, stopping at (and
+/// including) the first occupied square in each direction.
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    let tables = tables();
+    Bitboard(tables.bishop[square.index()].attacks(&tables.attacks, occupied.0))
+}
+
+/// See [`bishop_attacks`].
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    let tables = tables();
+    Bitboard(tables.rook[square.index()].attacks(&tables.attacks, occupied.0))
+}
+
+/// See [`bishop_attacks`].
+pub fn queen_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    bishop_attacks(square, occupied) | rook_attacks(square, occupied)
+}
+
+/// The squares strictly between `a` and `b`, or an empty set if they do not
+/// share a rank, file or diagonal.
+pub fn between(a: Square, b: Square) -> Bitboard {
+    Bitboard(BETWEEN[a.index()][b.index()])
+}
+
+/// The whole rank, file or diagonal through `a` and `b`, edge to edge, or
+/// an empty set if they do not share one.
+pub fn line(a: Square, b: Square) -> Bitboard {
+    Bitboard(LINE[a.index()][b.index()])
+}
+
+/// The attack table slice of one square.
+#[derive(Clone, Copy, Default)]
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+    use_pext: bool,
+}
+
+impl Magic {
+    fn index(&self, occupied: u64) -> usize {
+        if self.use_pext {
+            // SAFETY: use_pext is only set after detecting BMI2 at runtime
+            return self.offset + unsafe { pext(occupied, self.mask) } as usize;
+        }
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+
+    fn attacks(&self, table: &[u64], occupied: u64) -> u64 {
+        table[self.index(occupied)]
+    }
+}
+
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+#[target_feature(enable = "bmi2")]
+unsafe fn pext(value: u64, mask: u64) -> u64 {
+    std::arch::x86_64::_pext_u64(value, mask)
+}
+
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+fn has_pext() -> bool {
+    std::is_x86_feature_detected!("bmi2")
+}
+
+#[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+fn has_pext() -> bool {
+    false
+}
+
+#[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+unsafe fn pext(_value: u64, _mask: u64) -> u64 {
+    unreachable!("pext is only used when the CPU supports it")
+}
+
+struct Tables {
+    bishop: [Magic; 64],
+    rook: [Magic; 64],
+    attacks: Vec<u64>,
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(Tables::new)
+}
+
+impl Tables {
+    fn new() -> Tables {
+        let use_pext = has_pext();
+        let mut attacks = Vec::new();
+        let mut bishop = [Magic::default(); 64];
+        let mut rook = [Magic::default(); 64];
+        for square in 0..64 {
+            bishop[square] = build_square(
+                square,
+                &BISHOP_DIRECTIONS,
+                BISHOP_MAGICS[square],
+                use_pext,
+                &mut attacks,
+            );
+            rook[square] = build_square(
+                square,
+                &ROOK_DIRECTIONS,
+                ROOK_MAGICS[square],
+                use_pext,
+                &mut attacks,
+            );
+        }
+
+        Tables {
+            bishop,
+            rook,
+            attacks,
+        }
+    }
+}
+
+/// Appends the attack table of a slider on `square` to `attacks`.
+fn build_square(
+    square: usize,
+    directions: &[(i32, i32); 4],
+    magic: u64,
+    use_pext: bool,
+    attacks: &mut Vec<u64>,
+) -> Magic {
+    let mask = relevant_occupancy(square, directions);
+    let bits = mask.count_ones();
+    let entry = Magic {
+        mask,
+        magic,
+        shift: 64 - bits,
+        offset: attacks.len(),
+        use_pext,
+    };
+    attacks.resize(entry.offset + (1 << bits), 0);
+    // walk every subset of the mask
+    let mut occupied = 0u64;
+    loop {
+        let index = entry.index(occupied);
+        let attack = slide(square, directions, occupied);
+        // a magic may map two occupancies to one slot only when they give
+        // the same attacks
+        debug_assert!(attacks[index] == 0 || attacks[index] == attack);
+        attacks[index] = attack;
+        occupied = occupied.wrapping_sub(mask) & mask;
+        if occupied == 0 {
+            return entry;
+        }
+    }
+}
+
+/// The squares whose occupancy matters for a slider on `square`: its rays
+/// without the board edge they run into.
+fn relevant_occupancy(square: usize, directions: &[(i32, i32); 4]) -> u64 {
+    let mut mask = 0;
+    let (col, row) = ((square % 8) as i32, (square / 8) as i32);
+    for &(dc, dr) in directions {
+        let (mut c, mut r) = (col + dc, row + dr);
+        while (0..8).contains(&(c + dc)) && (0..8).contains(&(r + dr)) {
+            mask |= 1u64 << (r * 8 + c);
+            c += dc;
+            r += dr;
+        }
+    }
+    mask
+}
+
+/// Attacks of a slider on `square`, walking each ray until it leaves the
+/// board or hits an occupied square.
+const fn slide(square: usize, directions: &[(i32, i32); 4], occupied: u64) -> u64 {
+    let mut attacks = 0;
+    let (col, row) = ((square % 8) as i32, (square / 8) as i32);
+    let mut i = 0;
+    while i < directions.len() {
+        let (dc, dr) = directions[i];
+        let (mut c, mut r) = (col + dc, row + dr);
+        while c >= 0 && c < 8 && r >= 0 && r < 8 {
+            let target = 1u64 << (r * 8 + c);
+            attacks |= target;
+            if occupied & target != 0 {
+                break;
+            }
+            c += dc;
+            r += dr;
+        }
+        i += 1;
+    }
+    attacks
+}
+
+/// Attacks of a piece that jumps by fixed steps, for every square.
+const fn leaper_table<const N: usize>(steps: &[(i32, i32); N]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (col, row) = ((square % 8) as i32, (square / 8) as i32);
+        let mut i = 0;
+        while i < N {
+            let (c, r) = (col + steps[i].0, row + steps[i].1);
+            if c >= 0 && c < 8 && r >= 0 && r < 8 {
+                table[square] |= 1u64 << (r * 8 + c);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+/// The slider directions that connect `a` and `b`, if they share a rank,
+/// file or diagonal.
+const fn connecting_directions(a: usize, b: usize) -> Option<&'static [(i32, i32); 4]> {
+    if a == b {
+        None
+    } else if slide(a, &ROOK_DIRECTIONS, 0) & (1u64 << b) != 0 {
+        Some(&ROOK_DIRECTIONS)
+    } else if slide(a, &BISHOP_DIRECTIONS, 0) & (1u64 << b) != 0 {
+        Some(&BISHOP_DIRECTIONS)
+    } else {
+        None
+    }
+}
+
+const fn between_table() -> [[u64; 64]; 64] {
+    let mut table = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            if let Some(directions) = connecting_directions(a, b) {
+                table[a][b] = slide(a, directions, 1u64 << b) & slide(b, directions, 1u64 << a);
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+}
+
+const fn line_table() -> [[u64; 64]; 64] {
+    let mut table = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            if let Some(directions) = connecting_directions(a, b) {
+                table[a][b] =
+                    (slide(a, directions, 0) & slide(b, directions, 0)) | (1u64 << a) | (1u64 << b);
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+}
@@ -0,0 +1,58 @@
+//! Counts the legal move tree of a position, split up by the first move.
+//! It needs no graphics, so it builds with `--no-default-features`.
+
+use chess::Position;
+
+fn main() {
+    let mut fen: Option<String> = None;
+    let mut depth: Option<u32> = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fen" => fen = Some(args.next().unwrap_or_default()),
+            other => match other.parse() {
+                Ok(d) if depth.is_none() => depth = Some(d),
+                _ => exit_with_usage(&format!("unknown argument '{}'", other)),
+            },
+        }
+    }
+    let depth = depth.unwrap_or_else(|| exit_with_usage("perft needs a depth"));
+    let position = match fen {
+        Some(fen) => Position::from_fen(&fen).unwrap_or_else(|e| {
+            eprintln!("invalid FEN \"{}\": {}", fen, e);
+            std::process::exit(1);
+        }),
+        None => Position::new(),
+    };
+    run_perft(&position, depth);
+}
+
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("usage: perft <depth> [--fen \"<fen>\"]");
+    std::process::exit(1);
+}
+
+/// Prints the perft-divide node counts of `position` in the usual
+/// "move: nodes" format.
+fn run_perft(position: &Position, depth: u32) {
+    let start = std::time::Instant::now();
+    let divide = position.perft_divide(depth);
+    for (mv, nodes) in &divide {
+        println!("{}: {}", mv, nodes);
+    }
+    // there is no first move to split by at depth 0, where the count is 1
+    let total = if depth == 0 {
+        position.perft(0)
+    } else {
+        divide.iter().map(|(_, nodes)| nodes).sum()
+    };
+    let elapsed = start.elapsed();
+    println!();
+    println!("Nodes searched: {}", total);
+    println!(
+        "Time: {:.3}s ({:.0} nodes/s)",
+        elapsed.as_secs_f64(),
+        total as f64 / elapsed.as_secs_f64()
+    );
+}
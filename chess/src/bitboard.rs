@@ -0,0 +1,176 @@
+use crate::Square;
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr};
+
+/// A set of squares, one bit per square in [`Square::index`] order.
+///
+/// Iterating over a bitboard yields its squares from the lowest bit up,
+/// without changing the bitboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const NOT_A_FILE: Bitboard = Bitboard(0xfefefefefefefefe);
+    pub const NOT_B_FILE: Bitboard = Bitboard(0xfdfdfdfdfdfdfdfd);
+    pub const NOT_G_FILE: Bitboard = Bitboard(0xbfbfbfbfbfbfbfbf);
+    pub const NOT_H_FILE: Bitboard = Bitboard(0x7f7f7f7f7f7f7f7f);
+    pub const NOT_AB_FILE: Bitboard = Bitboard(Self::NOT_A_FILE.0 & Self::NOT_B_FILE.0);
+    pub const NOT_GH_FILE: Bitboard = Bitboard(Self::NOT_G_FILE.0 & Self::NOT_H_FILE.0);
+    pub const RANK_1: Bitboard = Bitboard(0x00000000000000ff);
+    pub const RANK_2: Bitboard = Bitboard(0x000000000000ff00);
+    pub const RANK_7: Bitboard = Bitboard(0x00ff000000000000);
+    pub const RANK_8: Bitboard = Bitboard(0xff00000000000000);
+    pub const DARK_SQUARES: Bitboard = Bitboard(0xaa55aa55aa55aa55);
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The number of squares in the set.
+    pub const fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub const fn contains(self, square: Square) -> bool {
+        self.0 & square.bitboard().0 != 0
+    }
+
+    /// The squares in the set, from the lowest bit up.
+    pub fn iter(self) -> SquareIter {
+        self.into_iter()
+    }
+
+    /// The square on the lowest set bit, if any.
+    pub const fn first(self) -> Option<Square> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(Square::from_index(self.0.trailing_zeros() as usize))
+        }
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = SquareIter;
+
+    fn into_iter(self) -> SquareIter {
+        SquareIter(self)
+    }
+}
+
+/// The squares of a [`Bitboard`], from the lowest bit up.
+#[derive(Debug, Clone)]
+pub struct SquareIter(Bitboard);
+
+impl Iterator for SquareIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        let square = self.0.first()?;
+        self.0.0 &= self.0.0 - 1;
+        Some(square)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.0.count() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for SquareIter {}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Bitboard {
+        square.bitboard()
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 | rhs.0)
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Bitboard;
+
+    fn bitxor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 ^ rhs.0)
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl Shl<u32> for Bitboard {
+    type Output = Bitboard;
+
+    fn shl(self, rhs: u32) -> Bitboard {
+        Bitboard(self.0 << rhs)
+    }
+}
+
+impl Shr<u32> for Bitboard {
+    type Output = Bitboard;
+
+    fn shr(self, rhs: u32) -> Bitboard {
+        Bitboard(self.0 >> rhs)
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Bitboard) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Bitboard) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitXorAssign for Bitboard {
+    fn bitxor_assign(&mut self, rhs: Bitboard) {
+        self.0 ^= rhs.0;
+    }
+}
+
+/// The board as eight lines of `X` and `.`, rank 8 first, a-file on the
+/// left.
+impl fmt::Display for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                let c = if self.contains(Square::new(file, rank)) {
+                    'X'
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+                if file < 7 {
+                    write!(f, " ")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use std::ops::{BitOr, BitOrAssign};
+
+/// A set of castling rights.
+///
+/// Rights are numbered 0 to 3 in the order white kingside, white queenside,
+/// black kingside, black queenside, which is the FEN order "KQkq" and the
+/// order of the Polyglot castling keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CastlingRights(u8);
+
+impl CastlingRights {
+    pub const NONE: CastlingRights = CastlingRights(0);
+    pub const WHITE_KINGSIDE: CastlingRights = CastlingRights(1);
+    pub const WHITE_QUEENSIDE: CastlingRights = CastlingRights(2);
+    pub const BLACK_KINGSIDE: CastlingRights = CastlingRights(4);
+    pub const BLACK_QUEENSIDE: CastlingRights = CastlingRights(8);
+    pub const ALL: CastlingRights = CastlingRights(15);
+
+    /// The single right numbered `index`.
+    pub const fn from_index(index: usize) -> CastlingRights {
+        CastlingRights(1 << index)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every right in `rights` is in the set.
+    pub const fn contains(self, rights: CastlingRights) -> bool {
+        self.0 & rights.0 == rights.0
+    }
+
+    pub fn insert(&mut self, rights: CastlingRights) {
+        self.0 |= rights.0;
+    }
+
+    pub fn remove(&mut self, rights: CastlingRights) {
+        self.0 &= !rights.0;
+    }
+
+    /// The numbers of the rights in the set, in ascending order.
+    pub fn indices(self) -> impl Iterator<Item = usize> {
+        (0..4).filter(move |&i| self.contains(CastlingRights::from_index(i)))
+    }
+}
+
+impl BitOr for CastlingRights {
+    type Output = CastlingRights;
+
+    fn bitor(self, rhs: CastlingRights) -> CastlingRights {
+        CastlingRights(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for CastlingRights {
+    fn bitor_assign(&mut self, rhs: CastlingRights) {
+        self.0 |= rhs.0;
+    }
+}
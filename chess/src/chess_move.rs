@@ -0,0 +1,96 @@
+use crate::{Color, Piece, PieceKind, Square};
+use std::fmt;
+
+/// A move for the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChessMove {
+    pub from_square: Square,
+    pub to_square: Square,
+    pub piece: Piece,
+    pub capture: Option<Piece>,
+    pub promotion: Option<PieceKind>,
+    pub kind: MoveKind,
+}
+
+/// The special moves that need more than moving a piece and removing what
+/// stood on its target square. Captures and promotions are not listed here;
+/// they show in [`ChessMove::capture`] and [`ChessMove::promotion`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveKind {
+    Normal,
+    /// A pawn moving two squares, which allows an en-passant reply.
+    DoublePawnPush,
+    KingsideCastle,
+    QueensideCastle,
+    EnPassant,
+}
+
+impl ChessMove {
+    pub fn new(from_square: Square, to_square: Square, piece: Piece) -> ChessMove {
+        ChessMove {
+            from_square,
+            to_square,
+            piece,
+            capture: None,
+            promotion: None,
+            kind: MoveKind::Normal,
+        }
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        self.promotion.is_some()
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.kind == MoveKind::EnPassant
+    }
+
+    /// The square the captured piece stands on. This differs from
+    /// `to_square` only for en-passant captures, where the captured pawn is
+    /// beside the capturing one.
+    pub fn captured_square(&self) -> Square {
+        if self.is_en_passant() {
+            Square::new(self.to_square.file(), self.from_square.rank())
+        } else {
+            self.to_square
+        }
+    }
+
+    pub fn is_castling(&self) -> bool {
+        matches!(
+            self.kind,
+            MoveKind::KingsideCastle | MoveKind::QueensideCastle
+        )
+    }
+
+    /// Number of the castling right used by this move, as in
+    /// [`CastlingRights::from_index`](crate::CastlingRights::from_index).
+    pub(crate) fn castling_index(&self) -> Option<usize> {
+        let side = if self.piece.color == Color::White {
+            0
+        } else {
+            2
+        };
+        match self.kind {
+            MoveKind::KingsideCastle => Some(side),
+            MoveKind::QueensideCastle => Some(side + 1),
+            _ => None,
+        }
+    }
+}
+
+/// UCI coordinate notation: "e2e4", or "e7e8q" for a promotion. See
+/// [`ChessMove::to_uci`] for the king-takes-rook castling form.
+impl fmt::Display for ChessMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from_square, self.to_square)?;
+        if let Some(promotion) = self.promotion {
+            write!(f, "{}", promotion.to_char())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{Bitboard, CastlingRights, Color, Piece, PieceKind, Position, Square};
+use std::fmt;
+
+/// Why a FEN string could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// FEN needs the four board fields, optionally followed by both clocks.
+    WrongFieldCount(usize),
+    /// The placement field does not have eight ranks.
+    WrongRankCount(usize),
+    /// A rank (1-8) describes more or fewer than eight squares.
+    WrongRankLength(u32),
+    InvalidPiece(char),
+    /// Each side needs exactly one king.
+    InvalidKingCount,
+    /// A pawn stands on the first or eighth rank.
+    PawnOnBackRank,
+    /// The side that just moved has left its king in check.
+    OpponentInCheck,
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::WrongFieldCount(count) => {
+                write!(f, "expected 4 or 6 fields, found {}", count)
+            }
+            FenError::WrongRankCount(count) => write!(f, "expected 8 ranks, found {}", count),
+            FenError::WrongRankLength(rank) => {
+                write!(f, "rank {} does not describe 8 squares", rank)
+            }
+            FenError::InvalidPiece(c) => write!(f, "invalid piece '{}'", c),
+            FenError::InvalidKingCount => write!(f, "each side needs exactly one king"),
+            FenError::PawnOnBackRank => write!(f, "a pawn stands on the first or eighth rank"),
+            FenError::OpponentInCheck => {
+                write!(f, "the side not to move is in check")
+            }
+            FenError::InvalidSideToMove(s) => write!(f, "invalid side to move '{}'", s),
+            FenError::InvalidCastling(s) => write!(f, "invalid castling rights '{}'", s),
+            FenError::InvalidEnPassant(s) => write!(f, "invalid en-passant square '{}'", s),
+            FenError::InvalidHalfmoveClock(s) => write!(f, "invalid halfmove clock '{}'", s),
+            FenError::InvalidFullmoveNumber(s) => {
+                write!(f, "invalid fullmove number '{}'", s)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// FEN letters in [`CastlingRights`] order.
+const CASTLING_CHARS: [char; 4] = ['K', 'Q', 'k', 'q'];
+
+impl Position {
+    /// Reads a position from Forsyth-Edwards Notation. The two clock fields
+    /// may be left out, in which case they default to "0 1".
+    pub fn from_fen(fen: &str) -> Result<Position, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+        let mut position = Position::empty();
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
+        for (row, rank_text) in ranks.iter().enumerate() {
+            let rank = 7 - row as u8;
+            let mut file = 0;
+            for c in rank_text.chars() {
+                if let Some(skip) = c.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                    file += skip as u8;
+                } else {
+                    let piece = Piece::from_char(c).ok_or(FenError::InvalidPiece(c))?;
+                    if file < 8 {
+                        position.bitboards[piece.index()] |= Square::new(file, rank).bitboard();
+                    }
+                    file += 1;
+                }
+                if file > 8 {
+                    return Err(FenError::WrongRankLength(rank as u32 + 1));
+                }
+            }
+            if file != 8 {
+                return Err(FenError::WrongRankLength(rank as u32 + 1));
+            }
+        }
+        if Color::ALL
+            .into_iter()
+            .any(|color| position.pieces(Piece::new(color, PieceKind::King)).count() != 1)
+        {
+            return Err(FenError::InvalidKingCount);
+        }
+        let pawns = position.pieces(Piece::new(Color::White, PieceKind::Pawn))
+            | position.pieces(Piece::new(Color::Black, PieceKind::Pawn));
+        if !(pawns & (Bitboard::RANK_1 | Bitboard::RANK_8)).is_empty() {
+            return Err(FenError::PawnOnBackRank);
+        }
+        position.update_occupancy();
+
+        position.side_to_move = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(FenError::InvalidSideToMove(other.to_string())),
+        };
+
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let right = CASTLING_CHARS
+                    .iter()
+                    .position(|&r| r == c)
+                    .map(CastlingRights::from_index)
+                    .filter(|&right| !position.castling_rights.contains(right))
+                    .ok_or_else(|| FenError::InvalidCastling(fields[2].to_string()))?;
+                position.castling_rights.insert(right);
+            }
+        }
+
+        let mover = position.side_to_move;
+        if position.is_square_attacked(position.king_square(!mover), mover) {
+            return Err(FenError::OpponentInCheck);
+        }
+
+        if fields[3] != "-" {
+            // the skipped square is on the sixth rank after a white double
+            // push and on the third rank after a black one, with the pawn
+            // that pushed just past it and its start square now empty
+            let (expected_rank, forward) = match mover {
+                Color::White => (5, -1),
+                Color::Black => (2, 1),
+            };
+            let square = Square::from_algebraic(fields[3])
+                .filter(|square| square.rank() == expected_rank)
+                .filter(|square| {
+                    let pawn = Square::new(square.file(), (square.rank() as i8 + forward) as u8);
+                    let start = Square::new(square.file(), (square.rank() as i8 - forward) as u8);
+                    position.piece_at(pawn) == Some(Piece::new(!mover, PieceKind::Pawn))
+                        && position.piece_at(*square).is_none()
+                        && position.piece_at(start).is_none()
+                })
+                .ok_or_else(|| FenError::InvalidEnPassant(fields[3].to_string()))?;
+            position.en_passant = Some(square);
+        }
+
+        if fields.len() == 6 {
+            position.halfmove_clock = fields[4]
+                .parse()
+                .map_err(|_| FenError::InvalidHalfmoveClock(fields[4].to_string()))?;
+            position.fullmove_number = fields[5]
+                .parse()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| FenError::InvalidFullmoveNumber(fields[5].to_string()))?;
+        }
+        position.zobrist_key = position.compute_zobrist_key();
+        Ok(position)
+    }
+
+    /// Writes the position in Forsyth-Edwards Notation.
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.piece_at(Square::new(file, rank)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece.to_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push_str(match self.side_to_move {
+            Color::White => " w ",
+            Color::Black => " b ",
+        });
+
+        if self.castling_rights.is_empty() {
+            fen.push('-');
+        }
+        for i in self.castling_rights.indices() {
+            fen.push(CASTLING_CHARS[i]);
+        }
+
+        fen.push(' ');
+        match self.en_passant {
+            Some(square) => fen.push_str(&square.to_string()),
+            None => fen.push('-'),
+        }
+
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+        fen
+    }
+}
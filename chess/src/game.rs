@@ -0,0 +1,289 @@
+use crate::{Bitboard, ChessMove, Color, Piece, PieceKind, Position, Undo};
+use std::fmt;
+
+/// Why a game ended in a draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    InsufficientMaterial,
+    /// Both players agreed to a draw.
+    Agreement,
+}
+
+/// The state of a game after the last move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate { winner: Color },
+    Stalemate,
+    Draw(DrawReason),
+    Resignation { winner: Color },
+    Timeout { winner: Color },
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        *self != GameStatus::Ongoing
+    }
+
+    /// The PGN result string: "1-0", "0-1", "1/2-1/2" or "*".
+    pub fn result(&self) -> &'static str {
+        match *self {
+            GameStatus::Ongoing => "*",
+            GameStatus::Checkmate { winner }
+            | GameStatus::Resignation { winner }
+            | GameStatus::Timeout { winner } => match winner {
+                Color::White => "1-0",
+                Color::Black => "0-1",
+            },
+            GameStatus::Stalemate | GameStatus::Draw(_) => "1/2-1/2",
+        }
+    }
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DrawReason::FiftyMoveRule => write!(f, "50-move rule"),
+            DrawReason::SeventyFiveMoveRule => write!(f, "75-move rule"),
+            DrawReason::ThreefoldRepetition => write!(f, "threefold repetition"),
+            DrawReason::FivefoldRepetition => write!(f, "fivefold repetition"),
+            DrawReason::InsufficientMaterial => write!(f, "insufficient material"),
+            DrawReason::Agreement => write!(f, "agreement"),
+        }
+    }
+}
+
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GameStatus::Ongoing => write!(f, "Game in progress"),
+            GameStatus::Checkmate { winner } => write!(f, "{} wins by checkmate", winner),
+            GameStatus::Stalemate => write!(f, "Draw by stalemate"),
+            GameStatus::Draw(reason) => write!(f, "Draw by {}", reason),
+            GameStatus::Resignation { winner } => write!(f, "{} wins by resignation", winner),
+            GameStatus::Timeout { winner } => write!(f, "{} wins on time", winner),
+        }
+    }
+}
+
+/// A game in progress: the current position, the moves and positions that
+/// led to it and how the game stands.
+#[derive(Debug, Clone)]
+pub struct Game {
+    start_position: Position,
+    position: Position,
+    history: Vec<u64>,
+    undos: Vec<Undo>,
+    redos: Vec<ChessMove>,
+    status: GameStatus,
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game::from_position(Position::new())
+    }
+
+    pub fn from_position(position: Position) -> Game {
+        let mut game = Game {
+            history: vec![position.repetition_key()],
+            undos: Vec::new(),
+            redos: Vec::new(),
+            start_position: position,
+            position,
+            status: GameStatus::Ongoing,
+        };
+        game.update_status();
+        game
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// The position the game started from.
+    pub fn start_position(&self) -> Position {
+        self.start_position
+    }
+
+    /// The moves played so far, not counting any taken back.
+    pub fn moves(&self) -> Vec<ChessMove> {
+        self.undos.iter().map(|undo| undo.mv).collect()
+    }
+
+    /// The move that led to the current position, if any.
+    pub fn last_move(&self) -> Option<ChessMove> {
+        self.undos.last().map(|undo| undo.mv)
+    }
+
+    /// Plays a move from [`Position::generate_moves`]. Moves are ignored once
+    /// the game is over.
+    pub fn make_move(&mut self, mv: ChessMove) {
+        if self.status.is_over() {
+            return;
+        }
+        self.redos.clear();
+        self.play(mv);
+    }
+
+    /// Takes back the last move, keeping it for [`Game::redo`]. An ended game
+    /// becomes playable again.
+    pub fn undo(&mut self) -> bool {
+        let Some(undo) = self.undos.pop() else {
+            return false;
+        };
+        self.position.unmake_move(undo);
+        self.history.pop();
+        self.redos.push(undo.mv);
+        self.update_status();
+        true
+    }
+
+    /// Replays the last move taken back with [`Game::undo`]. Nothing is
+    /// replayed once the game is over.
+    pub fn redo(&mut self) -> bool {
+        if self.status.is_over() {
+            return false;
+        }
+        let Some(mv) = self.redos.pop() else {
+            return false;
+        };
+        self.play(mv);
+        true
+    }
+
+    fn play(&mut self, mv: ChessMove) {
+        self.undos.push(self.position.make_move(mv));
+        self.history.push(self.position.repetition_key());
+        self.update_status();
+    }
+
+    /// How many times the current position has occurred, including now.
+    pub fn repetitions(&self) -> usize {
+        let key = self.position.repetition_key();
+        self.history.iter().filter(|&&k| k == key).count()
+    }
+
+    /// A draw the side to move may claim: threefold repetition or the
+    /// 50-move rule.
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        if self.status.is_over() {
+            None
+        } else if self.repetitions() >= 3 {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.position.halfmove_clock() >= 100 {
+            Some(DrawReason::FiftyMoveRule)
+        } else {
+            None
+        }
+    }
+
+    /// Ends the game in a draw if one can be claimed.
+    pub fn claim_draw(&mut self) -> bool {
+        match self.claimable_draw() {
+            Some(reason) => {
+                self.status = GameStatus::Draw(reason);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn update_status(&mut self) {
+        self.status = self.position.status();
+        if self.status == GameStatus::Ongoing && self.repetitions() >= 5 {
+            self.status = GameStatus::Draw(DrawReason::FivefoldRepetition);
+        }
+    }
+
+    /// Ends the game with `color` resigning.
+    pub fn resign(&mut self, color: Color) {
+        if !self.status.is_over() {
+            self.status = GameStatus::Resignation { winner: !color };
+        }
+    }
+
+    /// Ends the game in a draw agreed by both players.
+    pub fn agree_draw(&mut self) {
+        if !self.status.is_over() {
+            self.status = GameStatus::Draw(DrawReason::Agreement);
+        }
+    }
+
+    /// Ends the game with `color` running out of time.
+    pub fn timeout(&mut self, color: Color) {
+        if !self.status.is_over() {
+            self.status = GameStatus::Timeout { winner: !color };
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Position {
+    /// The status that follows from the position alone: mate, stalemate or
+    /// a draw that needs no claim.
+    pub fn status(&self) -> GameStatus {
+        if self.generate_moves().is_empty() {
+            if self.in_check() {
+                return GameStatus::Checkmate {
+                    winner: !self.side_to_move,
+                };
+            }
+            return GameStatus::Stalemate;
+        }
+        if self.halfmove_clock >= 150 {
+            return GameStatus::Draw(DrawReason::SeventyFiveMoveRule);
+        }
+        if self.is_insufficient_material() {
+            return GameStatus::Draw(DrawReason::InsufficientMaterial);
+        }
+        GameStatus::Ongoing
+    }
+
+    /// True when neither side can ever mate: bare kings, a single minor
+    /// piece, or only bishops that all stand on squares of one color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let both = |kind| {
+            Color::ALL.into_iter().fold(Bitboard::EMPTY, |acc, color| {
+                acc | self.pieces(Piece::new(color, kind))
+            })
+        };
+        let pawns_and_majors =
+            both(PieceKind::Pawn) | both(PieceKind::Rook) | both(PieceKind::Queen);
+        if !pawns_and_majors.is_empty() {
+            return false;
+        }
+        let knights = both(PieceKind::Knight);
+        let bishops = both(PieceKind::Bishop);
+        if (knights | bishops).count() <= 1 {
+            return true;
+        }
+        knights.is_empty()
+            && ((bishops & Bitboard::DARK_SQUARES).is_empty()
+                || (bishops & !Bitboard::DARK_SQUARES).is_empty())
+    }
+
+    /// The Zobrist key with the en-passant file left out when no en-passant
+    /// capture is legal, so that positions repeat exactly when the rules
+    /// say they do.
+    fn repetition_key(&self) -> u64 {
+        let en_passant_key = self.en_passant_key();
+        if en_passant_key != 0 && !self.generate_moves().iter().any(|mv| mv.is_en_passant()) {
+            self.zobrist_key ^ en_passant_key
+        } else {
+            self.zobrist_key
+        }
+    }
+}
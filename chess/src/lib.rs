@@ -0,0 +1,32 @@
+//! Bitboard chess rules engine.
+//!
+//! The engine has no graphics dependency; the ggez front end in `main.rs`
+//! is a thin layer over [`Game`] and [`Position`].
+
+pub mod attacks;
+mod bitboard;
+mod castling;
+mod chess_move;
+mod fen;
+mod game;
+mod movegen;
+mod perft;
+mod pgn;
+mod piece;
+mod position;
+mod san;
+mod square;
+mod uci;
+mod zobrist;
+
+pub use bitboard::{Bitboard, SquareIter};
+pub use castling::CastlingRights;
+pub use chess_move::{ChessMove, MoveKind};
+pub use fen::FenError;
+pub use game::{DrawReason, Game, GameStatus};
+pub use pgn::{PgnError, PgnGame, PgnNode};
+pub use piece::{Color, Piece, PieceKind};
+pub use position::{Position, Undo};
+pub use san::SanError;
+pub use square::Square;
+pub use uci::UciError;
@@ -1,18 +1,57 @@
 #![allow(dead_code)]
+use chess::{Bitboard, ChessMove, Game, PgnGame, Piece, PieceKind, Position, Square};
 use ggez::conf;
 use ggez::event::{self, EventHandler};
 use ggez::graphics::{self, Color};
+use ggez::input::keyboard::{KeyCode, KeyInput, KeyMods};
 use ggez::winit::window::CursorIcon;
 use ggez::{Context, ContextBuilder, GameResult};
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 fn main() {
+    let mut fen: Option<String> = None;
+    let mut pgn: Option<PathBuf> = None;
+    let mut flipped = false;
+    let mut auto_flip = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fen" => fen = Some(args.next().unwrap_or_default()),
+            "--pgn" => pgn = Some(PathBuf::from(args.next().unwrap_or_default())),
+            "--black" => flipped = true,
+            "--auto-flip" => auto_flip = true,
+            other => exit_with_usage(&format!("unknown argument '{}'", other)),
+        }
+    }
+    let position = match fen {
+        Some(fen) => Position::from_fen(&fen).unwrap_or_else(|e| {
+            eprintln!("invalid FEN \"{}\": {}", fen, e);
+            std::process::exit(1);
+        }),
+        None => Position::new(),
+    };
+    let loaded_pgn = pgn.map(|path| {
+        read_pgn(&path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        })
+    });
+    let game = match &loaded_pgn {
+        Some(pgn) => pgn.to_game(),
+        None => Game::from_position(position),
+    };
     let width: f32 = 640.0;
-    let height: f32 = 640.0;
+    let height: f32 = 640.0 + STATUS_BAR_HEIGHT;
     let assets = std::path::PathBuf::from("./assets");
     let (mut ctx, event_loop) = ContextBuilder::new("Chess", "Leasy")
         .add_resource_path(assets)
-        .window_mode(conf::WindowMode::default().dimensions(width, height))
+        .window_mode(
+            conf::WindowMode::default()
+                .dimensions(width, height)
+                .resizable(true)
+                .min_dimensions(160.0, 160.0 + STATUS_BAR_HEIGHT),
+        )
         .window_setup(conf::WindowSetup::default().title("Chess Bitboard"))
         .build()
         .expect("aieee, could not create ggez context!");
@@ -20,360 +59,620 @@ fn main() {
     // Create an instance of your event handler.
     // Usually, you should provide it with the Context object to
     // use when setting your game up.
-    let g = Chess::new(&mut ctx, width, height);
-    // Inside main() or after precompute:
-    // Check paths starting from Square 0 (A1)
-    // println!("--- Debugging Rook Paths from A1 (0) ---");
-    // let start_node = 0;
-    // if let Some(map) = g.rook_paths.get(start_node) {
-    //     for (target, path_mask) in map {
-    //         let title = format!("Path A1 -> Square {}", target);
-    //         print_bitboard(*path_mask, &title);
-    //     }
-    // }
+    let mut g = Chess::new(&mut ctx, game, width, height);
+    g.flipped = flipped;
+    g.auto_flip = auto_flip;
+    g.loaded_pgn = loaded_pgn;
     // Run!
     event::run(ctx, event_loop, g);
 }
 
-fn print_bitboard(bitboard: u64, name: &str) {
-    println!("=== {} ({}) ===", name, bitboard);
-    for rank in (0..8).rev() {
-        // Print rank 7 (top) down to 0
-        for file in 0..8 {
-            let square_index = rank * 8 + file;
-            let mask = 1u64 << square_index;
-            if (bitboard & mask) != 0 {
-                print!(" X "); // Bit is set
-            } else {
-                print!(" . "); // Empty
-            }
-        }
-        println!(); // New line for next rank
-    }
-    println!();
+/// The directory Ctrl+S saves new games in and Ctrl+O opens them from.
+const SAVE_DIR: &str = "games";
+
+/// How long a message such as "Saved to ..." stays on the screen.
+const NOTICE_TIME: Duration = Duration::from_secs(3);
+
+/// Height of the strip under the board that holds the draw-claim button.
+const STATUS_BAR_HEIGHT: f32 = 40.0;
+
+/// How long a piece dropped on a square it cannot reach takes to slide back.
+const SNAP_BACK_TIME: Duration = Duration::from_millis(250);
+
+/// Reads the first game in the PGN file at `path`.
+fn read_pgn(path: &Path) -> Result<PgnGame, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("could not open {}: {}", path.display(), e))?;
+    let pgn =
+        PgnGame::parse(&text).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+    Ok(pgn)
+}
+
+/// The first `games/game-NNN.pgn` not taken by an earlier save.
+fn new_save_path() -> PathBuf {
+    (1..)
+        .map(|n| Path::new(SAVE_DIR).join(format!("game-{:03}.pgn", n)))
+        .find(|path| !path.exists())
+        .expect("there is always an unused number")
+}
+
+/// The most recently written PGN file in the save directory.
+fn latest_save() -> Option<PathBuf> {
+    std::fs::read_dir(SAVE_DIR)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "pgn"))
+        .max_by_key(|path| path.metadata().and_then(|meta| meta.modified()).ok())
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct ChessMove {
-    from_square: u64,
-    to_square: u64,
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("usage: chess [--fen \"<fen>\" | --pgn <file>] [--black] [--auto-flip]");
+    std::process::exit(1);
 }
 
 struct Chess {
-    bishop_paths: [HashMap<u64, u64>; 64],
-    rook_paths: [HashMap<u64, u64>; 64],
-    possible_moves: [u64; 12],
-    occupancy: [u64; 2],
-    is_white_turn: bool,
+    game: Game,
     mouse_position: [f32; 2],
-    from_square: u64,
-    to_square: u64,
+    /// The selected square, which holds a piece of the side to move.
+    from_square: Option<Square>,
+    promotion_move: Option<ChessMove>,
+    /// Whether the board is turned to put Black at the bottom.
+    flipped: bool,
+    /// Whether the board turns to the side to move after every move, for two
+    /// players sharing the screen. `flipped` then turns it the other way.
+    auto_flip: bool,
+    /// The legal moves of the selected piece.
+    piece_moves: Vec<ChessMove>,
+    /// Whether the selected piece is held down and follows the mouse.
+    dragging: bool,
+    /// Set when the held piece was already selected, so that letting go of
+    /// it on its own square deselects it.
+    deselect_on_release: bool,
+    snap_back: Option<SnapBack>,
+    /// The file the game was last saved to. A file the game was opened from
+    /// is never written, as it may hold other games.
+    pgn_path: Option<PathBuf>,
+    /// The record the game was opened from, whose tags and annotations are
+    /// kept when it is saved.
+    loaded_pgn: Option<PgnGame>,
+    /// A message for the player and when it was shown.
+    notice: Option<(String, Instant)>,
     width: f32,
     height: f32,
-    bitboards: [u64; 12],
     images: [graphics::Image; 12],
+    /// The side of a square: the largest that fits the board in the window.
     square_size: f32,
-    texture_size: f32,
-    square_mesh: graphics::Mesh,
+    /// Top-left corner of the board, which is centered in the window.
+    board_origin: [f32; 2],
+    theme: Theme,
+    meshes: BoardMeshes,
+}
+
+/// Shapes sized to one square, tinted through [`graphics::DrawParam::color`].
+/// They are rebuilt whenever the square size changes.
+struct BoardMeshes {
+    square: graphics::Mesh,
+    dot: graphics::Mesh,
+    ring: graphics::Mesh,
+    glow: graphics::Mesh,
+}
+
+impl BoardMeshes {
+    fn new(ctx: &mut Context, square_size: f32) -> BoardMeshes {
+        let rect = graphics::Rect::new(0.0, 0.0, square_size, square_size);
+        let center = [square_size / 2.0, square_size / 2.0];
+        // faint circles stacked on each other, so the glow fades outwards
+        let mut glow = graphics::MeshBuilder::new();
+        for k in 0..5 {
+            let radius = square_size * (0.5 - 0.06 * k as f32);
+            glow.circle(
+                graphics::DrawMode::fill(),
+                center,
+                radius,
+                0.5,
+                Color::new(1.0, 1.0, 1.0, 0.2),
+            )
+            .expect("Could not make the glow");
+        }
+        BoardMeshes {
+            square: graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                rect,
+                Color::WHITE,
+            )
+            .expect("Could not make the rectangle"),
+            dot: graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::fill(),
+                center,
+                square_size * 0.16,
+                0.5,
+                Color::WHITE,
+            )
+            .expect("Could not make the dot"),
+            ring: graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::stroke(square_size * 0.08),
+                center,
+                square_size * 0.46,
+                0.5,
+                Color::WHITE,
+            )
+            .expect("Could not make the ring"),
+            glow: graphics::Mesh::from_data(ctx, glow.build()),
+        }
+    }
+}
+
+/// The colors of the board. Highlights are drawn over the square colors in
+/// the order the fields are listed, so they should be translucent.
+struct Theme {
+    light_square: Color,
+    dark_square: Color,
+    last_move: Color,
+    /// Under the king of the side to move while it is in check.
+    check: Color,
+    selected: Color,
+    /// The square a piece was dropped back on after an illegal move.
+    snap_back: Color,
+    hover: Color,
+    /// The dots and rings on the squares the selected piece can move to.
+    move_hint: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            light_square: Color::from_rgb(233, 233, 233),
+            dark_square: Color::from_rgb(70, 70, 70),
+            last_move: Color::from_rgba(170, 190, 60, 120),
+            check: Color::from_rgb(230, 30, 30),
+            selected: Color::from_rgba(220, 200, 60, 150),
+            snap_back: Color::from_rgba(230, 25, 25, 150),
+            hover: Color::from_rgba(255, 255, 255, 50),
+            move_hint: Color::from_rgba(40, 40, 40, 110),
+        }
+    }
+}
+
+/// A piece sliding back to its square after an illegal drop.
+struct SnapBack {
+    piece: Piece,
+    square: Square,
+    /// Where the piece was let go, as the top-left corner of its cell.
+    dropped_at: [f32; 2],
+    started: Instant,
+}
+
+impl SnapBack {
+    /// How far the piece has travelled back, from 0 to 1.
+    fn progress(&self) -> f32 {
+        (self.started.elapsed().as_secs_f32() / SNAP_BACK_TIME.as_secs_f32()).min(1.0)
+    }
 }
 
 impl Chess {
-    pub const NOT_A_FILE: u64 = 0x7f7f7f7f7f7f7f7f;
-    pub const NOT_B_FILE: u64 = 0xbfbfbfbfbfbfbfbf;
-    pub const NOT_G_FILE: u64 = 0xfdfdfdfdfdfdfdfd;
-    pub const NOT_H_FILE: u64 = 0xfefefefefefefefe;
-    pub const NOT_AB_FILE: u64 = Self::NOT_A_FILE & Self::NOT_B_FILE;
-    pub const NOT_GH_FILE: u64 = Self::NOT_G_FILE & Self::NOT_H_FILE;
-    fn make_move(&mut self) {
-        if self.from_square == 0 || self.to_square == 0 || self.from_square == self.to_square {
-            return;
+    /// Plays the move from `from` to `to`. Returns false if there is no such
+    /// legal move.
+    fn make_move(&mut self, from: Square, to: Square) -> bool {
+        let Some(mv) = self.game.position().find_move(from, to) else {
+            return false;
+        };
+        if mv.is_promotion() {
+            // wait for the piece choice before committing the move
+            self.promotion_move = Some(mv);
+        } else {
+            self.game.make_move(mv);
         }
-        if !self.is_move_possible(ChessMove {
-            from_square: self.from_square,
-            to_square: self.to_square,
-        }) {
-            return;
+        true
+    }
+
+    /// Selects the piece on `square`, or clears the selection, and collects
+    /// the piece's legal moves.
+    fn select(&mut self, square: Option<Square>) {
+        self.from_square = square;
+        self.dragging = false;
+        self.deselect_on_release = false;
+        self.piece_moves = match square {
+            Some(square) => self
+                .game
+                .position()
+                .generate_moves()
+                .into_iter()
+                .filter(|mv| mv.from_square == square)
+                .collect(),
+            None => Vec::new(),
+        };
+    }
+
+    /// Whether `square` holds a piece of the side to move.
+    fn is_own_piece(&self, square: Option<Square>) -> bool {
+        let position = self.game.position();
+        square
+            .and_then(|square| position.piece_at(square))
+            .is_some_and(|piece| piece.color == position.side_to_move())
+    }
+
+    /// Writes the game to the file it was saved to before, or else to a new
+    /// file, so that no other game is overwritten.
+    fn save_game(&mut self) {
+        let path = self.pgn_path.clone().unwrap_or_else(new_save_path);
+        let pgn = match &self.loaded_pgn {
+            Some(loaded) => loaded.with_game(&self.game),
+            None => PgnGame::from_game(&self.game),
         }
-        for i in 0..12 {
-            if self.bitboards[i] & self.to_square != 0 {
-                self.bitboards[i] ^= self.to_square;
+        .to_pgn();
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, pgn));
+        match written {
+            Ok(()) => {
+                self.show_notice(format!("Saved to {}", path.display()));
+                self.pgn_path = Some(path);
             }
+            Err(e) => self.show_notice(format!("Could not save {}: {}", path.display(), e)),
         }
-        for i in 0..12 {
-            if self.bitboards[i] & self.from_square != 0 {
-                self.bitboards[i] ^= self.from_square;
-                self.bitboards[i] |= self.to_square;
+    }
+
+    /// Replaces the game with the most recently saved one. Returns true if
+    /// it was loaded.
+    fn open_game(&mut self) -> bool {
+        let Some(path) = latest_save() else {
+            self.show_notice(format!("There are no saved games in {}", SAVE_DIR));
+            return false;
+        };
+        match read_pgn(&path) {
+            Ok(pgn) => {
+                self.game = pgn.to_game();
+                self.loaded_pgn = Some(pgn);
+                self.pgn_path = None;
+                self.show_notice(format!("Opened {}", path.display()));
+                true
             }
-        }
-        self.occupancy[0] = self.bitboards[0]
-            | self.bitboards[1]
-            | self.bitboards[2]
-            | self.bitboards[3]
-            | self.bitboards[4]
-            | self.bitboards[5];
-        self.occupancy[1] = self.bitboards[6]
-            | self.bitboards[7]
-            | self.bitboards[8]
-            | self.bitboards[9]
-            | self.bitboards[10]
-            | self.bitboards[11];
-        self.is_white_turn = !self.is_white_turn;
-        self.update_possible_moves();
-    }
-
-    fn is_move_possible(&self, mv: ChessMove) -> bool {
-        for piece_index in 0..self.bitboards.len() {
-            if self.bitboards[piece_index] & mv.from_square != 0 {
-                if (piece_index < 6) != self.is_white_turn {
-                    return false;
-                }
-                return match piece_index % 6 {
-                    0 => self.is_pawn_move_possible(mv),
-                    1 => self.is_knight_move_possible(mv),
-                    2 => self.is_bishop_move_possible(mv),
-                    3 => self.is_rook_move_possible(mv),
-                    4 => self.is_queen_move_possible(mv),
-                    5 => self.is_king_move_possible(mv),
-                    _ => false,
-                };
+            Err(e) => {
+                self.show_notice(e);
+                false
             }
         }
-        false
     }
 
-    fn is_pawn_move_possible(&self, mv: ChessMove) -> bool {
-        let side_index = if self.is_white_turn { 0 } else { 1 };
-        let single_move = [mv.from_square << 8, mv.from_square >> 8];
-        let double_move = [mv.from_square << 16, mv.from_square >> 16];
-        let take_right = [mv.from_square << 7, mv.from_square >> 9];
-        let take_left = [mv.from_square << 9, mv.from_square >> 7];
-        let moves = single_move[side_index]
-            | double_move[side_index]
-            | take_right[side_index]
-            | take_left[side_index];
-        return moves & mv.to_square & !self.occupancy[side_index] != 0;
+    fn show_notice(&mut self, text: String) {
+        self.notice = Some((text, Instant::now()));
     }
 
-    fn is_knight_move_possible(&self, mv: ChessMove) -> bool {
-        let side_index = if self.is_white_turn { 0 } else { 1 };
-        let mut moves: u64 = 0;
-        if (mv.from_square & Self::NOT_AB_FILE) != 0 {
-            moves |= (mv.from_square << 10) | (mv.from_square >> 6);
-        }
-        if (mv.from_square & Self::NOT_GH_FILE) != 0 {
-            moves |= (mv.from_square << 6) | (mv.from_square >> 10);
+    /// The side whose first rank is at the bottom of the screen.
+    fn bottom_color(&self) -> chess::Color {
+        let color = if self.auto_flip {
+            self.game.position().side_to_move()
+        } else {
+            chess::Color::White
+        };
+        if self.flipped { !color } else { color }
+    }
+
+    /// Maps a file and rank to a screen column and row, counted from the
+    /// top-left corner, and back again: the mapping is its own inverse. It
+    /// is the one place that decides how the board faces the player.
+    fn view_transform(&self, a: u8, b: u8) -> (u8, u8) {
+        match self.bottom_color() {
+            chess::Color::White => (a, 7 - b),
+            chess::Color::Black => (7 - a, b),
         }
-        if (mv.from_square & Self::NOT_A_FILE) != 0 {
-            moves |= (mv.from_square << 17) | (mv.from_square >> 15);
+    }
+
+    /// Top-left corner of `square` on the screen.
+    fn square_origin(&self, square: Square) -> [f32; 2] {
+        let (column, row) = self.view_transform(square.file(), square.rank());
+        [
+            self.board_origin[0] + column as f32 * self.square_size,
+            self.board_origin[1] + row as f32 * self.square_size,
+        ]
+    }
+
+    /// The square under the screen point `x`, `y`, if any.
+    fn square_at(&self, x: f32, y: f32) -> Option<Square> {
+        let board_x = x - self.board_origin[0];
+        let board_y = y - self.board_origin[1];
+        if board_x < 0.0 || board_y < 0.0 {
+            return None;
         }
-        if (mv.from_square & Self::NOT_H_FILE) != 0 {
-            moves |= (mv.from_square << 15) | (mv.from_square >> 17);
+        let column = (board_x / self.square_size) as u8;
+        let row = (board_y / self.square_size) as u8;
+        if column > 7 || row > 7 {
+            return None;
         }
-        return (moves & mv.to_square) != 0 && (mv.to_square & self.occupancy[side_index]) == 0;
+        let (file, rank) = self.view_transform(column, row);
+        Some(Square::new(file, rank))
     }
 
-    fn is_bishop_move_possible(&self, mv: ChessMove) -> bool {
-        let side_index = if self.is_white_turn { 0 } else { 1 };
-        let from_index = mv.from_square.trailing_zeros() as usize;
-        let to_index = mv.to_square.trailing_zeros() as u64;
-        if let Some(path_mask) = self.bishop_paths[from_index].get(&to_index) {
-            let all_pieces = self.occupancy[0] | self.occupancy[1];
-            if (path_mask & all_pieces) != 0 {
-                return false;
-            }
-            if (mv.to_square & self.occupancy[side_index]) != 0 {
-                return false;
-            }
-            return true;
-        }
-        false
-    }
-
-    fn is_rook_move_possible(&self, mv: ChessMove) -> bool {
-        let side_index = if self.is_white_turn { 0 } else { 1 };
-        let from_index = mv.from_square.trailing_zeros() as usize;
-        let to_index = mv.to_square.trailing_zeros() as u64;
-        if let Some(path_mask) = self.rook_paths[from_index].get(&to_index) {
-            let all_pieces = self.occupancy[0] | self.occupancy[1];
-            // if (path_mask & (all_pieces & !mv.to_square)) != 0 {
-            // path mask does not containt the source and target of the rook move
-            // so the next check should be ok
-            if (path_mask & all_pieces) != 0 {
-                return false;
-            }
-            if (mv.to_square & self.occupancy[side_index]) != 0 {
-                return false;
+    /// The pieces offered for a promotion, queen first, each with the
+    /// top-left corner of its dialog cell. The cells start on the promotion
+    /// square and run towards the middle of the board.
+    fn promotion_choices(&self, mv: ChessMove) -> [(Piece, [f32; 2]); 4] {
+        let [x, y] = self.square_origin(mv.to_square);
+        let step = if y < self.height / 2.0 {
+            self.square_size
+        } else {
+            -self.square_size
+        };
+        std::array::from_fn(|k| {
+            let piece = Piece::new(mv.piece.color, PieceKind::PROMOTIONS[k]);
+            (piece, [x, y + k as f32 * step])
+        })
+    }
+
+    /// Commits the pending promotion if `x`, `y` hit one of the offered
+    /// pieces; any other click cancels it.
+    fn choose_promotion(&mut self, x: f32, y: f32) {
+        if let Some(mv) = self.promotion_move.take() {
+            for (piece, [left, top]) in self.promotion_choices(mv) {
+                if (left..left + self.square_size).contains(&x)
+                    && (top..top + self.square_size).contains(&y)
+                {
+                    self.game.make_move(ChessMove {
+                        promotion: Some(piece.kind),
+                        ..mv
+                    });
+                }
             }
-            return true;
         }
-        false
     }
 
-    fn is_queen_move_possible(&self, mv: ChessMove) -> bool {
-        return self.is_bishop_move_possible(mv) || self.is_rook_move_possible(mv);
+    /// Screen area of the draw-claim button, shown while a draw is
+    /// claimable. It fills the status bar, which the board never overlaps.
+    fn claim_button_rect(&self) -> graphics::Rect {
+        graphics::Rect::new(
+            0.0,
+            self.height - STATUS_BAR_HEIGHT,
+            self.width,
+            STATUS_BAR_HEIGHT,
+        )
     }
 
-    fn is_king_move_possible(&self, mv: ChessMove) -> bool {
-        false
+    fn draw_claim_button(&mut self, canvas: &mut graphics::Canvas) {
+        let Some(reason) = self.game.claimable_draw() else {
+            return;
+        };
+        let rect = self.claim_button_rect();
+        let button = graphics::DrawParam::default()
+            .dest(rect.point())
+            .scale([rect.w / self.square_size, rect.h / self.square_size])
+            .color(Color::from_rgba(30, 60, 120, 220));
+        canvas.draw(&self.meshes.square, button);
+        let mut text = graphics::Text::new(format!("Claim draw by {}", reason));
+        text.set_scale(rect.h * 0.5)
+            .set_layout(graphics::TextLayout::center());
+        let param = graphics::DrawParam::default()
+            .dest(rect.center())
+            .color(Color::WHITE);
+        canvas.draw(&text, param);
     }
 
-    fn update_possible_moves(&mut self) {
-        self.gen_pawn_moves(self.is_white_turn);
-        self.gen_knight_moves(self.is_white_turn);
-        self.gen_bishop_moves(self.is_white_turn);
-        self.gen_rook_moves(self.is_white_turn);
-        self.gen_queen_moves(self.is_white_turn);
-        self.gen_king_moves(self.is_white_turn);
+    /// Shows the current notice in a strip across the top of the window.
+    fn draw_notice(&mut self, canvas: &mut graphics::Canvas) {
+        let Some((notice, _)) = &self.notice else {
+            return;
+        };
+        let strip = graphics::DrawParam::default()
+            .scale([
+                self.width / self.square_size,
+                STATUS_BAR_HEIGHT / self.square_size,
+            ])
+            .color(Color::from_rgba(20, 20, 20, 220));
+        canvas.draw(&self.meshes.square, strip);
+        let mut text = graphics::Text::new(notice.as_str());
+        text.set_scale(STATUS_BAR_HEIGHT * 0.45)
+            .set_layout(graphics::TextLayout::center());
+        let param = graphics::DrawParam::default()
+            .dest([self.width / 2.0, STATUS_BAR_HEIGHT / 2.0])
+            .color(Color::WHITE);
+        canvas.draw(&text, param);
     }
 
-    fn gen_pawn_moves(&mut self, is_white: bool) {
-        let side_index: usize = if is_white { 0 } else { 1 };
-        let empty_squares = !(self.occupancy[0] | self.occupancy[1]);
-        let enemy_squares = self.occupancy[1 - side_index];
-        let single_move = [
-            self.bitboards[6 * side_index] << 8,
-            self.bitboards[6 * side_index] >> 8,
-        ];
-        let double_move = [
-            self.bitboards[6 * side_index] << 16,
-            self.bitboards[6 * side_index] >> 16,
-        ];
-        let take_right = [
-            self.bitboards[6 * side_index] << 7,
-            self.bitboards[6 * side_index] >> 9,
-        ];
-        let take_left = [
-            self.bitboards[6 * side_index] << 9,
-            self.bitboards[6 * side_index] >> 7,
-        ];
-        self.possible_moves[6 * side_index] = (empty_squares & single_move[side_index])
-            | (empty_squares & double_move[side_index])
-            | (enemy_squares & take_right[side_index] & Self::NOT_H_FILE)
-            | (enemy_squares & take_left[side_index] & Self::NOT_A_FILE);
-    }
-
-    fn gen_knight_moves(&mut self, is_white: bool) {
-        let side_index = if is_white { 1 } else { 7 };
-        self.possible_moves[side_index] = ((self.bitboards[side_index] << 6) & Self::NOT_AB_FILE)
-            | ((self.bitboards[side_index] << 10) & Self::NOT_GH_FILE)
-            | ((self.bitboards[side_index] << 15) & Self::NOT_A_FILE)
-            | ((self.bitboards[side_index] << 17) & Self::NOT_H_FILE)
-            | ((self.bitboards[side_index] >> 6) & Self::NOT_GH_FILE)
-            | ((self.bitboards[side_index] >> 10) & Self::NOT_AB_FILE)
-            | ((self.bitboards[side_index] >> 15) & Self::NOT_A_FILE)
-            | ((self.bitboards[side_index] >> 17) & Self::NOT_H_FILE);
-    }
-    fn gen_bishop_moves(&mut self, is_white: bool) {}
-    fn gen_rook_moves(&mut self, is_white: bool) {}
-    fn gen_queen_moves(&mut self, is_white: bool) {}
-    fn gen_king_moves(&mut self, is_white: bool) {}
-
-    fn precompute_bishop_paths(&mut self) {
-        for i in 0..64 {
-            let start_x = (i % 8) as i8;
-            let start_y = (i / 8) as i8;
-            for t in 0..64 {
-                if i == t {
-                    continue;
-                }
-                let target_x = (t % 8) as i8;
-                let target_y = (t / 8) as i8;
-                let dx = target_x - start_x;
-                let dy = target_y - start_y;
-                if dx.abs() == dy.abs() {
-                    let steps = dx.abs();
-                    let step_x = dx.signum();
-                    let step_y = dy.signum();
-                    let jump_index = step_y * 8 + step_x;
-                    let path = (1..steps).fold(0, |acc, k| {
-                        let square_idx = (i as i8 + k as i8 * jump_index) as u64;
-                        acc | (1u64 << square_idx)
-                    });
-                    self.bishop_paths[i as usize].insert(t, path);
-                }
-            }
+    fn draw_result_banner(&mut self, canvas: &mut graphics::Canvas) {
+        let status = self.game.status();
+        if !status.is_over() {
+            return;
         }
+        let banner_height = self.square_size * 1.5;
+        let banner = graphics::DrawParam::default()
+            .dest([0.0, (self.height - banner_height) / 2.0])
+            .scale([
+                self.width / self.square_size,
+                banner_height / self.square_size,
+            ])
+            .color(Color::from_rgba(20, 20, 20, 220));
+        canvas.draw(&self.meshes.square, banner);
+        let mut text = graphics::Text::new(format!("{}\n{}", status, status.result()));
+        text.set_scale(self.square_size * 0.4)
+            .set_layout(graphics::TextLayout::center());
+        let param = graphics::DrawParam::default()
+            .dest([self.width / 2.0, self.height / 2.0])
+            .color(Color::WHITE);
+        canvas.draw(&text, param);
     }
 
-    fn precompute_rook_paths(&mut self) {
-        for i in 0..64 {
-            let to_i0: u64 = i % 8;
-            let to_j0: u64 = i / 8;
-            for k in 0..8 {
-                let to_hor = k + to_j0 * 8;
-                let to_ver = to_i0 + k * 8;
-                if to_hor != i {
-                    let mini = to_i0.min(k);
-                    let maxi = to_i0.max(k);
-                    let to_hor_path = (mini + 1..maxi).fold(0, |a, b| a | (1 << (b + to_j0 * 8)));
-                    self.rook_paths[i as usize].insert(to_hor, to_hor_path);
-                }
-                if to_ver != i {
-                    let minj = to_j0.min(k);
-                    let maxj = to_j0.max(k);
-                    let to_ver_path = (minj + 1..maxj).fold(0, |a, b| a | (1 << (to_i0 + b * 8)));
-                    self.rook_paths[i as usize].insert(to_ver, to_ver_path);
-                }
-            }
+    fn draw_promotion_dialog(&mut self, canvas: &mut graphics::Canvas) {
+        let Some(mv) = self.promotion_move else {
+            return;
+        };
+        let shade = graphics::DrawParam::default()
+            .scale([
+                self.width / self.square_size,
+                self.height / self.square_size,
+            ])
+            .color(Color::from_rgba(0, 0, 0, 140));
+        canvas.draw(&self.meshes.square, shade);
+        for (piece, [x, y]) in self.promotion_choices(mv) {
+            let param = graphics::DrawParam::default()
+                .dest([x, y])
+                .color(Color::from_rgb(200, 200, 160));
+            canvas.draw(&self.meshes.square, param);
+            self.draw_piece(canvas, piece, [x, y]);
         }
     }
 
+    /// Paints the squares and, over them, the last move, a glow under a
+    /// king in check, the selected square and the square under the mouse.
     fn draw_board(&mut self, canvas: &mut graphics::Canvas) {
-        for i in 0..8 {
-            for j in 0..8 {
-                let color = if (i + j) % 2 == 0 {
-                    Color::from_rgb(233, 233, 233)
-                } else {
-                    Color::from_rgb(70, 70, 70)
-                };
-                let x = i as f32 * self.square_size;
-                let y = j as f32 * self.square_size;
-                let param = graphics::DrawParam::default().dest([x, y]).color(color);
-                canvas.draw(&self.square_mesh, param);
+        for index in 0..64 {
+            let square = Square::from_index(index);
+            let color = if Bitboard::DARK_SQUARES.contains(square) {
+                self.theme.dark_square
+            } else {
+                self.theme.light_square
+            };
+            self.tint_square(canvas, square, color);
+        }
+        if let Some(mv) = self.game.last_move() {
+            self.tint_square(canvas, mv.from_square, self.theme.last_move);
+            self.tint_square(canvas, mv.to_square, self.theme.last_move);
+        }
+        let position = self.game.position();
+        if position.in_check() {
+            let king = position.king_square(position.side_to_move());
+            let param = graphics::DrawParam::default()
+                .dest(self.square_origin(king))
+                .color(self.theme.check);
+            canvas.draw(&self.meshes.glow, param);
+        }
+        if let Some(snap_back) = &self.snap_back {
+            let mut color = self.theme.snap_back;
+            color.a *= 1.0 - snap_back.progress();
+            self.tint_square(canvas, snap_back.square, color);
+        } else if let Some(square) = self.from_square {
+            self.tint_square(canvas, square, self.theme.selected);
+        }
+        if let Some(square) = self.hovered_square() {
+            self.tint_square(canvas, square, self.theme.hover);
+        }
+    }
+
+    fn tint_square(&self, canvas: &mut graphics::Canvas, square: Square, color: Color) {
+        let param = graphics::DrawParam::default()
+            .dest(self.square_origin(square))
+            .color(color);
+        canvas.draw(&self.meshes.square, param);
+    }
+
+    /// The square under the mouse if clicking it would do something: pick
+    /// up a piece of the side to move or move the selected piece there.
+    fn hovered_square(&self) -> Option<Square> {
+        if self.promotion_move.is_some() || self.game.status().is_over() {
+            return None;
+        }
+        let [x, y] = self.mouse_position;
+        let square = self.square_at(x, y)?;
+        let target = self.piece_moves.iter().any(|mv| mv.to_square == square);
+        (target || self.is_own_piece(Some(square))).then_some(square)
+    }
+
+    /// Marks where the selected piece can go: a dot on an empty square and a
+    /// ring around a piece it can capture.
+    fn draw_move_hints(&mut self, canvas: &mut graphics::Canvas) {
+        let mut quiet = Bitboard::EMPTY;
+        let mut captures = Bitboard::EMPTY;
+        for mv in &self.piece_moves {
+            if mv.is_capture() {
+                captures |= mv.to_square.bitboard();
+            } else {
+                quiet |= mv.to_square.bitboard();
+            }
+        }
+        for (squares, mesh) in [(quiet, &self.meshes.dot), (captures, &self.meshes.ring)] {
+            for square in squares {
+                let param = graphics::DrawParam::default()
+                    .dest(self.square_origin(square))
+                    .color(self.theme.move_hint);
+                canvas.draw(mesh, param);
             }
         }
     }
 
     fn draw_pieces(&mut self, canvas: &mut graphics::Canvas) {
-        let original_size = 128.0;
-        let scale = 0.6;
-        let offset = (self.square_size - original_size * scale) / 2.0;
-        let mut moving_piece_index: Option<usize> = None;
-        let mut moving_piece_x: f32 = 0.0;
-        let mut moving_piece_y: f32 = 0.0;
-        for piece_index in 0..self.images.len() {
-            let mut temp = self.bitboards[piece_index];
-            while temp != 0 {
-                let one_index = temp.trailing_zeros();
-                let i = one_index % 8;
-                let j = one_index / 8;
-                let x = self.width - (i + 1) as f32 * self.square_size;
-                let y = self.height - (j + 1) as f32 * self.square_size;
-                if one_index == self.from_square.trailing_zeros() {
-                    moving_piece_index = Some(piece_index);
-                    moving_piece_x = self.mouse_position[0] - self.square_size / 2.0;
-                    moving_piece_y = self.mouse_position[1] - self.square_size / 2.0;
-                    temp &= temp - 1;
+        let snapping = self.snap_back.as_ref().map(|snap_back| snap_back.square);
+        for piece in Piece::ALL {
+            for square in self.game.position().pieces(piece) {
+                let dragged = self.dragging && Some(square) == self.from_square;
+                if dragged || Some(square) == snapping {
                     continue;
                 }
-                let param = graphics::DrawParam::default()
-                    .dest([x + offset, y + offset])
-                    .scale([scale, scale]);
-                canvas.draw(&self.images[piece_index], param);
-                temp &= temp - 1;
+                self.draw_piece(canvas, piece, self.square_origin(square));
             }
         }
-        if let Some(piece_index) = moving_piece_index {
-            let param = graphics::DrawParam::default()
-                .dest([moving_piece_x, moving_piece_y])
-                .scale([scale, scale]);
-            canvas.draw(&self.images[piece_index], param);
-        }
     }
 
-    pub fn new(_ctx: &mut Context, width: f32, height: f32) -> Chess {
-        let square_size: f32 = 80.0;
-        let rect = graphics::Rect::new(0.0, 0.0, square_size, square_size);
+    /// Draws the piece under the mouse, or the one sliding back after an
+    /// illegal drop, above everything else on the board.
+    fn draw_moving_piece(&mut self, canvas: &mut graphics::Canvas) {
+        let (piece, origin) = if let Some(snap_back) = &self.snap_back {
+            let t = snap_back.progress();
+            let [x, y] = self.square_origin(snap_back.square);
+            let [x0, y0] = snap_back.dropped_at;
+            let dest = [x0 + (x - x0) * t, y0 + (y - y0) * t];
+            (snap_back.piece, dest)
+        } else if let Some(piece) = self
+            .from_square
+            .filter(|_| self.dragging)
+            .and_then(|square| self.game.position().piece_at(square))
+        {
+            (piece, self.dragged_piece_origin())
+        } else {
+            return;
+        };
+        self.draw_piece(canvas, piece, origin);
+    }
+
+    /// Fits the board to a window of `width` by `height`.
+    fn resize(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+        (self.square_size, self.board_origin) = Chess::board_layout(width, height);
+        self.meshes = BoardMeshes::new(ctx, self.square_size);
+    }
+
+    /// The square size and top-left corner of the board in a window of
+    /// `width` by `height`, leaving the status bar free.
+    fn board_layout(width: f32, height: f32) -> (f32, [f32; 2]) {
+        let height = height - STATUS_BAR_HEIGHT;
+        let square_size = (width.min(height) / 8.0).floor().max(1.0);
+        let board_size = square_size * 8.0;
+        let origin = [
+            ((width - board_size) / 2.0).floor(),
+            ((height - board_size) / 2.0).floor(),
+        ];
+        (square_size, origin)
+    }
+
+    /// Draws `piece` centered in the square-sized cell with its top-left
+    /// corner at `origin`, scaled to fill most of the cell.
+    fn draw_piece(&self, canvas: &mut graphics::Canvas, piece: Piece, origin: [f32; 2]) {
+        let image = &self.images[piece.index()];
+        let size = image.width().max(image.height()) as f32;
+        let scale = self.square_size * 0.96 / size;
+        let [x, y] = origin;
+        let param = graphics::DrawParam::default()
+            .dest([
+                x + (self.square_size - image.width() as f32 * scale) / 2.0,
+                y + (self.square_size - image.height() as f32 * scale) / 2.0,
+            ])
+            .scale([scale, scale]);
+        canvas.draw(image, param);
+    }
+
+    /// Top-left corner of the cell of the piece being dragged.
+    fn dragged_piece_origin(&self) -> [f32; 2] {
+        [
+            self.mouse_position[0] - self.square_size / 2.0,
+            self.mouse_position[1] - self.square_size / 2.0,
+        ]
+    }
+
+    pub fn new(_ctx: &mut Context, game: Game, width: f32, height: f32) -> Chess {
         let bpawn_image =
             graphics::Image::from_path(_ctx, "/bpawn.png").expect("Could not load image");
         let bknight_image =
@@ -398,58 +697,23 @@ impl Chess {
             graphics::Image::from_path(_ctx, "/wqueen.png").expect("Could not load image");
         let wking_image =
             graphics::Image::from_path(_ctx, "/wking.png").expect("Could not load image");
-        let wpawn_bitmask = 1u64 << 8
-            | 1u64 << 9
-            | 1u64 << 10
-            | 1u64 << 11
-            | 1u64 << 12
-            | 1u64 << 13
-            | 1u64 << 14
-            | 1u64 << 15;
-        let wknight_bitmask = 1u64 << 1 | 1u64 << 6;
-        let wbishop_bitmask = 1u64 << 2 | 1u64 << 5;
-        let wrook_bitmask = 1u64 << 0 | 1u64 << 7;
-        let wqueen_bitmask = 1u64 << 4;
-        let wking_bitmask = 1u64 << 3;
-        let bpawn_bitmask = 1u64 << 48
-            | 1u64 << 49
-            | 1u64 << 50
-            | 1u64 << 51
-            | 1u64 << 52
-            | 1u64 << 53
-            | 1u64 << 54
-            | 1u64 << 55;
-        let bknight_bitmask = 1u64 << 57 | 1u64 << 62;
-        let bbishop_bitmask = 1u64 << 58 | 1u64 << 61;
-        let brook_bitmask = 1u64 << 56 | 1u64 << 63;
-        let bqueen_bitmask = 1u64 << 60;
-        let bking_bitmask = 1u64 << 59;
-        let empty_rook_paths: [HashMap<u64, u64>; 64] = std::array::from_fn(|_| HashMap::new());
-        let empty_bishop_paths: [HashMap<u64, u64>; 64] = std::array::from_fn(|_| HashMap::new());
-        let mut chess = Chess {
-            bishop_paths: empty_bishop_paths,
-            rook_paths: empty_rook_paths,
-            occupancy: [
-                wpawn_bitmask
-                    | wknight_bitmask
-                    | wbishop_bitmask
-                    | wrook_bitmask
-                    | wqueen_bitmask
-                    | wking_bitmask,
-                bpawn_bitmask
-                    | bknight_bitmask
-                    | bbishop_bitmask
-                    | brook_bitmask
-                    | bqueen_bitmask
-                    | bking_bitmask,
-            ],
-            possible_moves: [0; 12],
-            is_white_turn: true,
+        let (square_size, board_origin) = Chess::board_layout(width, height);
+        Chess {
+            game,
             mouse_position: [0.0, 0.0],
-            from_square: 0,
-            to_square: 0,
-            width: width,
-            height: height,
+            from_square: None,
+            promotion_move: None,
+            flipped: false,
+            auto_flip: false,
+            piece_moves: Vec::new(),
+            dragging: false,
+            deselect_on_release: false,
+            snap_back: None,
+            pgn_path: None,
+            loaded_pgn: None,
+            notice: None,
+            width,
+            height,
             images: [
                 wpawn_image,
                 wknight_image,
@@ -464,51 +728,69 @@ impl Chess {
                 bqueen_image,
                 bking_image,
             ],
-            bitboards: [
-                wpawn_bitmask,
-                wknight_bitmask,
-                wbishop_bitmask,
-                wrook_bitmask,
-                wqueen_bitmask,
-                wking_bitmask,
-                bpawn_bitmask,
-                bknight_bitmask,
-                bbishop_bitmask,
-                brook_bitmask,
-                bqueen_bitmask,
-                bking_bitmask,
-            ],
-            texture_size: 64.0,
             square_size,
-            square_mesh: graphics::Mesh::new_rectangle(
-                _ctx,
-                graphics::DrawMode::fill(),
-                rect,
-                Color::WHITE,
-            )
-            .expect("Could not make the rectangle"),
-        };
-        chess.precompute_bishop_paths();
-        chess.precompute_rook_paths();
-        chess.update_possible_moves();
-        return chess;
-    }
-}
-
-pub fn get_square_mask(_x: f32, _y: f32, square_size: f32) -> u64 {
-    let i = (_x / square_size) as u64;
-    let j = (_y / square_size) as u64;
-    if i > 7 || j > 7 {
-        return 0;
+            board_origin,
+            theme: Theme::default(),
+            meshes: BoardMeshes::new(_ctx, square_size),
+        }
     }
-    let j = 7 - j;
-    let i = 7 - i;
-    1u64 << i + j * 8
 }
 
 impl EventHandler for Chess {
     fn update(&mut self, _ctx: &mut Context) -> GameResult {
-        // Update code here...
+        if self
+            .snap_back
+            .as_ref()
+            .is_some_and(|snap_back| snap_back.progress() >= 1.0)
+        {
+            self.snap_back = None;
+        }
+        if self
+            .notice
+            .as_ref()
+            .is_some_and(|(_, shown)| shown.elapsed() >= NOTICE_TIME)
+        {
+            self.notice = None;
+        }
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        input: KeyInput,
+        _repeated: bool,
+    ) -> Result<(), ggez::GameError> {
+        let ctrl = input.mods.contains(KeyMods::CTRL);
+        let stepped = match input.keycode {
+            Some(KeyCode::Escape) => {
+                ctx.request_quit();
+                false
+            }
+            Some(KeyCode::Z) if ctrl => self.game.undo(),
+            Some(KeyCode::Y) if ctrl => self.game.redo(),
+            Some(KeyCode::S) if ctrl => {
+                self.save_game();
+                false
+            }
+            Some(KeyCode::O) if ctrl => self.open_game(),
+            Some(KeyCode::F) => {
+                self.flipped = !self.flipped;
+                false
+            }
+            Some(KeyCode::A) => {
+                self.auto_flip = !self.auto_flip;
+                false
+            }
+            Some(KeyCode::Left) => self.game.undo(),
+            Some(KeyCode::Right) => self.game.redo(),
+            _ => false,
+        };
+        if stepped {
+            self.promotion_move = None;
+            self.snap_back = None;
+            self.select(None);
+        }
         Ok(())
     }
 
@@ -519,8 +801,41 @@ impl EventHandler for Chess {
         _x: f32,
         _y: f32,
     ) -> Result<(), ggez::GameError> {
-        if _button == event::MouseButton::Left {
-            self.from_square = get_square_mask(_x, _y, self.square_size);
+        if self.game.status().is_over() {
+            return Ok(());
+        }
+        if self.promotion_move.is_some() {
+            self.choose_promotion(_x, _y);
+            return Ok(());
+        }
+        if self.game.claimable_draw().is_some() && self.claim_button_rect().contains([_x, _y]) {
+            self.game.claim_draw();
+            return Ok(());
+        }
+        self.snap_back = None;
+        if _button == event::MouseButton::Right {
+            self.select(None);
+            _ctx.gfx.window().set_cursor_icon(CursorIcon::Default);
+            return Ok(());
+        }
+        if _button != event::MouseButton::Left {
+            return Ok(());
+        }
+        let square = self.square_at(_x, _y);
+        if let (Some(from), Some(to)) = (self.from_square, square)
+            && self.piece_moves.iter().any(|mv| mv.to_square == to)
+        {
+            self.make_move(from, to);
+            self.select(None);
+        } else if self.is_own_piece(square) {
+            if square == self.from_square {
+                self.deselect_on_release = true;
+            } else {
+                self.select(square);
+            }
+            self.dragging = true;
+        } else {
+            self.select(None);
         }
         Ok(())
     }
@@ -532,13 +847,32 @@ impl EventHandler for Chess {
         _x: f32,
         _y: f32,
     ) -> Result<(), ggez::GameError> {
-        if _button == event::MouseButton::Left {
-            self.to_square = get_square_mask(_x, _y, self.square_size);
-            self.make_move();
-            _ctx.gfx.window().set_cursor_icon(CursorIcon::Default);
+        if _button != event::MouseButton::Left || !self.dragging {
+            return Ok(());
+        }
+        _ctx.gfx.window().set_cursor_icon(CursorIcon::Default);
+        self.dragging = false;
+        let Some(from) = self.from_square else {
+            return Ok(());
+        };
+        let to = self.square_at(_x, _y);
+        if to == Some(from) {
+            // a click: keep the piece selected, unless it already was
+            if self.deselect_on_release {
+                self.select(None);
+            }
+        } else {
+            let moved = to.is_some_and(|to| self.make_move(from, to));
+            if !moved && let Some(piece) = self.game.position().piece_at(from) {
+                self.snap_back = Some(SnapBack {
+                    piece,
+                    square: from,
+                    dropped_at: self.dragged_piece_origin(),
+                    started: Instant::now(),
+                });
+            }
+            self.select(None);
         }
-        self.from_square = 0;
-        self.to_square = 0;
         Ok(())
     }
 
@@ -551,16 +885,32 @@ impl EventHandler for Chess {
         _dy: f32,
     ) -> Result<(), ggez::GameError> {
         self.mouse_position = [_x, _y];
-        if self.from_square != 0 {
+        if self.dragging {
             _ctx.gfx.window().set_cursor_icon(CursorIcon::Grab);
         }
         Ok(())
     }
 
+    fn resize_event(
+        &mut self,
+        ctx: &mut Context,
+        width: f32,
+        height: f32,
+    ) -> Result<(), ggez::GameError> {
+        self.resize(ctx, width, height);
+        Ok(())
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
         self.draw_board(&mut canvas);
         self.draw_pieces(&mut canvas);
+        self.draw_move_hints(&mut canvas);
+        self.draw_moving_piece(&mut canvas);
+        self.draw_claim_button(&mut canvas);
+        self.draw_promotion_dialog(&mut canvas);
+        self.draw_result_banner(&mut canvas);
+        self.draw_notice(&mut canvas);
         canvas.finish(ctx)
     }
 }
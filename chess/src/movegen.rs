@@ -0,0 +1,263 @@
+use crate::attacks::{
+    between, bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks,
+    rook_attacks,
+};
+use crate::{
+    Bitboard, CastlingRights, ChessMove, Color, MoveKind, Piece, PieceKind, Position, Square,
+};
+
+impl Position {
+    /// All legal moves for the side to move.
+    pub fn generate_moves(&self) -> Vec<ChessMove> {
+        let mut moves = self.generate_pseudo_legal_moves();
+        moves.retain(|&mv| self.is_legal(mv));
+        moves
+    }
+
+    /// Moves that follow the piece movement rules but may leave the mover's
+    /// king in check.
+    pub fn generate_pseudo_legal_moves(&self) -> Vec<ChessMove> {
+        let mut moves = Vec::with_capacity(64);
+        self.gen_pawn_moves(&mut moves);
+        self.gen_knight_moves(&mut moves);
+        self.gen_bishop_moves(&mut moves);
+        self.gen_rook_moves(&mut moves);
+        self.gen_queen_moves(&mut moves);
+        self.gen_king_moves(&mut moves);
+        moves
+    }
+
+    /// Returns true if the side to move is in check.
+    pub fn in_check(&self) -> bool {
+        let king_square = self.king_square(self.side_to_move);
+        self.is_square_attacked(king_square, !self.side_to_move)
+    }
+
+    /// Returns true if any piece of color `by` attacks `square`.
+    pub fn is_square_attacked(&self, square: Square, by: Color) -> bool {
+        self.is_square_attacked_on(&self.bitboards, square, by)
+    }
+
+    /// Every square attacked by the pieces of color `by`.
+    pub fn attacks(&self, by: Color) -> Bitboard {
+        let occupied = self.occupied();
+        let mut attacks = Bitboard::EMPTY;
+        for kind in PieceKind::ALL {
+            for square in self.pieces(Piece::new(by, kind)) {
+                attacks |= match kind {
+                    PieceKind::Pawn => pawn_attacks(square, by),
+                    PieceKind::Knight => knight_attacks(square),
+                    PieceKind::Bishop => bishop_attacks(square, occupied),
+                    PieceKind::Rook => rook_attacks(square, occupied),
+                    PieceKind::Queen => queen_attacks(square, occupied),
+                    PieceKind::King => king_attacks(square),
+                };
+            }
+        }
+        attacks
+    }
+
+    /// A move is legal when it does not leave the mover's own king attacked.
+    fn is_legal(&self, mv: ChessMove) -> bool {
+        let mut bitboards = self.bitboards;
+        Self::move_pieces(&mut bitboards, mv);
+        let king = Piece::new(self.side_to_move, PieceKind::King);
+        let king_square = bitboards[king.index()]
+            .first()
+            .expect("each side has a king");
+        !self.is_square_attacked_on(&bitboards, king_square, !self.side_to_move)
+    }
+
+    fn is_square_attacked_on(&self, bitboards: &[Bitboard; 12], square: Square, by: Color) -> bool {
+        let occupied = bitboards
+            .iter()
+            .fold(Bitboard::EMPTY, |acc, &bitboard| acc | bitboard);
+        let pieces = |kind| bitboards[Piece::new(by, kind).index()];
+        let queens = pieces(PieceKind::Queen);
+        // a pawn of the attacking side stands where a pawn of the other side
+        // on `square` would capture
+        !(pawn_attacks(square, !by) & pieces(PieceKind::Pawn)).is_empty()
+            || !(knight_attacks(square) & pieces(PieceKind::Knight)).is_empty()
+            || !(bishop_attacks(square, occupied) & (pieces(PieceKind::Bishop) | queens)).is_empty()
+            || !(rook_attacks(square, occupied) & (pieces(PieceKind::Rook) | queens)).is_empty()
+            || !(king_attacks(square) & pieces(PieceKind::King)).is_empty()
+    }
+
+    pub fn is_move_possible(&self, mv: ChessMove) -> bool {
+        self.generate_moves().contains(&mv)
+    }
+
+    /// Looks up the legal move going from `from_square` to `to_square`.
+    /// Moving the king onto one of its own rooks is read as castling.
+    pub fn find_move(&self, from_square: Square, to_square: Square) -> Option<ChessMove> {
+        let moves = self.generate_moves();
+        moves
+            .iter()
+            .find(|mv| mv.from_square == from_square && mv.to_square == to_square)
+            .or_else(|| {
+                moves.iter().find(|mv| {
+                    mv.from_square == from_square
+                        && mv
+                            .castling_index()
+                            .is_some_and(|i| Self::CASTLING_SQUARES[i][2] == to_square)
+                })
+            })
+            .copied()
+    }
+
+    /// The piece of `kind` belonging to the side to move.
+    fn own(&self, kind: PieceKind) -> Piece {
+        Piece::new(self.side_to_move, kind)
+    }
+
+    fn gen_pawn_moves(&self, moves: &mut Vec<ChessMove>) {
+        let color = self.side_to_move;
+        let piece = self.own(PieceKind::Pawn);
+        let empty_squares = !self.occupied();
+        let enemy_squares = self.occupancy(!color);
+        let (start_rank, forward): (_, fn(Bitboard) -> Bitboard) = match color {
+            Color::White => (Bitboard::RANK_2, |b| b << 8),
+            Color::Black => (Bitboard::RANK_7, |b| b >> 8),
+        };
+        for from_square in self.pieces(piece) {
+            let single_move = forward(from_square.bitboard()) & empty_squares;
+            // a double push needs the start rank and an empty square in between
+            let double_move = if start_rank.contains(from_square) {
+                forward(single_move) & empty_squares
+            } else {
+                Bitboard::EMPTY
+            };
+            let captures = pawn_attacks(from_square, color) & enemy_squares;
+            self.push_pawn_moves(moves, from_square, piece, single_move | captures);
+            if let Some(to_square) = double_move.first() {
+                let mut mv = ChessMove::new(from_square, to_square, piece);
+                mv.kind = MoveKind::DoublePawnPush;
+                moves.push(mv);
+            }
+            // is_legal removes the captured pawn as well, which also catches
+            // the case of both pawns shielding their king along a rank
+            if let Some(en_passant) = self.en_passant
+                && pawn_attacks(from_square, color).contains(en_passant)
+            {
+                let mut mv = ChessMove::new(from_square, en_passant, piece);
+                mv.capture = Some(Piece::new(!color, PieceKind::Pawn));
+                mv.kind = MoveKind::EnPassant;
+                moves.push(mv);
+            }
+        }
+    }
+
+    fn gen_knight_moves(&self, moves: &mut Vec<ChessMove>) {
+        let piece = self.own(PieceKind::Knight);
+        for from_square in self.pieces(piece) {
+            self.push_moves(moves, from_square, piece, knight_attacks(from_square));
+        }
+    }
+
+    fn gen_bishop_moves(&self, moves: &mut Vec<ChessMove>) {
+        let piece = self.own(PieceKind::Bishop);
+        let occupied = self.occupied();
+        for from_square in self.pieces(piece) {
+            let targets = bishop_attacks(from_square, occupied);
+            self.push_moves(moves, from_square, piece, targets);
+        }
+    }
+
+    fn gen_rook_moves(&self, moves: &mut Vec<ChessMove>) {
+        let piece = self.own(PieceKind::Rook);
+        let occupied = self.occupied();
+        for from_square in self.pieces(piece) {
+            let targets = rook_attacks(from_square, occupied);
+            self.push_moves(moves, from_square, piece, targets);
+        }
+    }
+
+    fn gen_queen_moves(&self, moves: &mut Vec<ChessMove>) {
+        let piece = self.own(PieceKind::Queen);
+        let occupied = self.occupied();
+        for from_square in self.pieces(piece) {
+            let targets = queen_attacks(from_square, occupied);
+            self.push_moves(moves, from_square, piece, targets);
+        }
+    }
+
+    fn gen_king_moves(&self, moves: &mut Vec<ChessMove>) {
+        let piece = self.own(PieceKind::King);
+        for from_square in self.pieces(piece) {
+            self.push_moves(moves, from_square, piece, king_attacks(from_square));
+        }
+        self.gen_castling_moves(moves);
+    }
+
+    fn gen_castling_moves(&self, moves: &mut Vec<ChessMove>) {
+        let side_index = self.side_to_move.index();
+        let king = self.own(PieceKind::King);
+        let rook = self.own(PieceKind::Rook);
+        let occupied = self.occupied();
+        for i in 2 * side_index..2 * side_index + 2 {
+            if !self.castling_rights.contains(CastlingRights::from_index(i)) {
+                continue;
+            }
+            let [king_from, king_to, rook_from, _] = Self::CASTLING_SQUARES[i];
+            if !self.pieces(king).contains(king_from) || !self.pieces(rook).contains(rook_from) {
+                continue;
+            }
+            if !(between(king_from, rook_from) & occupied).is_empty() {
+                continue;
+            }
+            // the king may not castle out of, through or into check
+            let mut king_path = king_from.bitboard() | king_to.bitboard();
+            king_path |= between(king_from, king_to);
+            if king_path
+                .iter()
+                .any(|square| self.is_square_attacked(square, !self.side_to_move))
+            {
+                continue;
+            }
+            let mut mv = ChessMove::new(king_from, king_to, king);
+            mv.kind = if i % 2 == 0 {
+                MoveKind::KingsideCastle
+            } else {
+                MoveKind::QueensideCastle
+            };
+            moves.push(mv);
+        }
+    }
+
+    /// Pushes a move from `from_square` to every square in `targets` that is
+    /// not occupied by the side to move.
+    fn push_moves(
+        &self,
+        moves: &mut Vec<ChessMove>,
+        from_square: Square,
+        piece: Piece,
+        targets: Bitboard,
+    ) {
+        for to_square in targets & !self.occupancy(self.side_to_move) {
+            let mut mv = ChessMove::new(from_square, to_square, piece);
+            mv.capture = self.piece_at(to_square);
+            moves.push(mv);
+        }
+    }
+
+    /// Like [`Position::push_moves`], but a pawn reaching the last rank is
+    /// emitted once for each promotion piece, queen first.
+    fn push_pawn_moves(
+        &self,
+        moves: &mut Vec<ChessMove>,
+        from_square: Square,
+        piece: Piece,
+        targets: Bitboard,
+    ) {
+        let promotions = targets & (Bitboard::RANK_1 | Bitboard::RANK_8);
+        self.push_moves(moves, from_square, piece, targets & !promotions);
+        for to_square in promotions & !self.occupancy(self.side_to_move) {
+            for promotion in PieceKind::PROMOTIONS {
+                let mut mv = ChessMove::new(from_square, to_square, piece);
+                mv.capture = self.piece_at(to_square);
+                mv.promotion = Some(promotion);
+                moves.push(mv);
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{ChessMove, Position};
+
+impl Position {
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep.
+    pub fn perft(&self, depth: u32) -> u64 {
+        let mut position = *self;
+        position.perft_nodes(depth)
+    }
+
+    /// Like [`Position::perft`], but split up by the first move, which makes
+    /// it easy to track a wrong count down to the move that causes it.
+    pub fn perft_divide(&self, depth: u32) -> Vec<(ChessMove, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let mut position = *self;
+        self.generate_moves()
+            .into_iter()
+            .map(|mv| {
+                let undo = position.make_move(mv);
+                let nodes = position.perft_nodes(depth - 1);
+                position.unmake_move(undo);
+                (mv, nodes)
+            })
+            .collect()
+    }
+
+    fn perft_nodes(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.generate_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for mv in moves {
+            let undo = self.make_move(mv);
+            nodes += self.perft_nodes(depth - 1);
+            self.unmake_move(undo);
+        }
+        nodes
+    }
+}
@@ -0,0 +1,532 @@
+use crate::{ChessMove, Color, FenError, Game, Position, SanError};
+use std::fmt;
+
+/// The tags every PGN game carries, in the order they are written.
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// Movetext lines are wrapped to stay within this many characters.
+const LINE_WIDTH: usize = 79;
+
+/// Why a PGN text could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    UnterminatedComment,
+    /// A tag pair is not of the form `[Name "value"]`.
+    InvalidTag(String),
+    InvalidFen(FenError),
+    IllegalMove {
+        san: String,
+        error: SanError,
+    },
+    /// A variation is closed without being opened or never closed.
+    UnbalancedParenthesis,
+    /// The text holds no game at all.
+    NoGame,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::UnterminatedComment => write!(f, "comment is never closed"),
+            PgnError::InvalidTag(tag) => write!(f, "invalid tag pair '{}'", tag),
+            PgnError::InvalidFen(error) => write!(f, "invalid FEN tag: {}", error),
+            PgnError::IllegalMove { san, error } => write!(f, "move '{}': {}", san, error),
+            PgnError::UnbalancedParenthesis => write!(f, "unbalanced variation parentheses"),
+            PgnError::NoGame => write!(f, "no game found"),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+/// One move of a game tree with the annotations written around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnNode {
+    pub mv: ChessMove,
+    /// Numeric annotation glyphs, such as 1 for "!" or 2 for "?".
+    pub nags: Vec<u8>,
+    /// A comment written before the move, at the start of a line.
+    pub comment_before: Option<String>,
+    /// A comment written after the move.
+    pub comment: Option<String>,
+    /// Alternatives to this move, each played from the position before it.
+    pub variations: Vec<Vec<PgnNode>>,
+}
+
+impl PgnNode {
+    pub fn new(mv: ChessMove) -> PgnNode {
+        PgnNode {
+            mv,
+            nags: Vec::new(),
+            comment_before: None,
+            comment: None,
+            variations: Vec::new(),
+        }
+    }
+}
+
+/// A game as recorded in PGN: its tags, starting position, main line with
+/// any variations, and result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    /// Tag pairs in the order they were read or set.
+    pub tags: Vec<(String, String)>,
+    pub start_position: Position,
+    pub moves: Vec<PgnNode>,
+    /// "1-0", "0-1", "1/2-1/2" or "*".
+    pub result: String,
+}
+
+impl PgnGame {
+    /// Records the moves played in `game` so far. The seven-tag roster is
+    /// filled with unknown values for the caller to replace.
+    pub fn from_game(game: &Game) -> PgnGame {
+        let mut pgn = PgnGame {
+            tags: Vec::new(),
+            start_position: game.start_position(),
+            moves: game.moves().into_iter().map(PgnNode::new).collect(),
+            result: game.status().result().to_string(),
+        };
+        for (name, value) in SEVEN_TAG_ROSTER {
+            pgn.set_tag(name, value);
+        }
+        pgn.set_tag("Result", game.status().result());
+        if pgn.start_position != Position::new() {
+            let fen = pgn.start_position.to_fen();
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", &fen);
+        }
+        pgn
+    }
+
+    /// Replays the main line into a [`Game`]. A result the moves do not
+    /// explain, such as "1-0" without mate, ends the game by resignation,
+    /// by timeout if the Termination tag says "time forfeit", or by agreed
+    /// draw.
+    pub fn to_game(&self) -> Game {
+        let mut game = Game::from_position(self.start_position);
+        for node in &self.moves {
+            game.make_move(node.mv);
+        }
+        let on_time = self
+            .tag("Termination")
+            .is_some_and(|termination| termination.eq_ignore_ascii_case("time forfeit"));
+        let loser = match self.result.as_str() {
+            "1-0" => Some(Color::Black),
+            "0-1" => Some(Color::White),
+            "1/2-1/2" => {
+                game.agree_draw();
+                None
+            }
+            _ => None,
+        };
+        match loser {
+            Some(color) if on_time => game.timeout(color),
+            Some(color) => game.resign(color),
+            None => {}
+        }
+        game
+    }
+
+    /// This record brought up to date with `game`, which was loaded from it
+    /// and may have been played on since. The tags are kept, and so are the
+    /// comments, NAGs and variations of the moves the two still share.
+    pub fn with_game(&self, game: &Game) -> PgnGame {
+        let mut pgn = PgnGame::from_game(game);
+        for (name, value) in &self.tags {
+            if !matches!(name.as_str(), "Result" | "SetUp" | "FEN") {
+                pgn.set_tag(name, value);
+            }
+        }
+        if pgn.start_position == self.start_position {
+            for (node, old) in pgn.moves.iter_mut().zip(&self.moves) {
+                if node.mv != old.mv {
+                    break;
+                }
+                *node = old.clone();
+            }
+        }
+        pgn
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets a tag, replacing its value if it is already present.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Reads every game in `text`.
+    ///
+    /// The reader is lenient about layout: comments in braces or after a
+    /// semicolon, numeric annotation glyphs, "!" and "?" suffixes, nested
+    /// variations, missing move numbers and results, and `%` escape lines
+    /// are all accepted.
+    pub fn parse_all(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            index: 0,
+        };
+        let mut games = Vec::new();
+        while parser.index < parser.tokens.len() {
+            games.push(parser.game()?);
+        }
+        Ok(games)
+    }
+
+    /// Reads the first game in `text`.
+    pub fn parse(text: &str) -> Result<PgnGame, PgnError> {
+        Self::parse_all(text)?
+            .into_iter()
+            .next()
+            .ok_or(PgnError::NoGame)
+    }
+
+    /// Writes the game in PGN export format: the seven-tag roster first,
+    /// then the other tags, then the movetext in SAN wrapped to 80 columns.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value = if name == "Result" {
+                &self.result
+            } else {
+                self.tag(name).unwrap_or(default)
+            };
+            pgn.push_str(&tag_pair(name, value));
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.iter().any(|(roster, _)| roster == name) {
+                pgn.push_str(&tag_pair(name, value));
+            }
+        }
+        pgn.push('\n');
+
+        let mut tokens = line_tokens(self.start_position, &self.moves);
+        tokens.push(self.result.clone());
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+        pgn
+    }
+}
+
+fn tag_pair(name: &str, value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{} \"{}\"]\n", name, value)
+}
+
+/// The movetext of `nodes` played from `position`, in pieces that may be
+/// wrapped onto separate lines.
+fn line_tokens(mut position: Position, nodes: &[PgnNode]) -> Vec<String> {
+    let mut tokens = Vec::new();
+    // black moves get a "12..." number at the start of a line and after
+    // anything that interrupts the moves
+    let mut needs_number = true;
+    // comments are split into words so that long ones can be wrapped
+    let push_comment = |tokens: &mut Vec<String>, comment: &str| {
+        let comment = format!("{{{}}}", comment.trim());
+        tokens.extend(comment.split_whitespace().map(str::to_string));
+    };
+    for node in nodes {
+        if let Some(comment) = &node.comment_before {
+            push_comment(&mut tokens, comment);
+            needs_number = true;
+        }
+        let number = position.fullmove_number();
+        let san = node.mv.to_san(&position);
+        match position.side_to_move() {
+            Color::White => tokens.push(format!("{}. {}", number, san)),
+            Color::Black if needs_number => tokens.push(format!("{}... {}", number, san)),
+            Color::Black => tokens.push(san),
+        }
+        needs_number = false;
+        tokens.extend(node.nags.iter().map(|nag| format!("${}", nag)));
+        if let Some(comment) = &node.comment {
+            push_comment(&mut tokens, comment);
+            needs_number = true;
+        }
+        for variation in &node.variations {
+            let mut variation = line_tokens(position, variation);
+            if let Some(first) = variation.first_mut() {
+                first.insert(0, '(');
+            }
+            if let Some(last) = variation.last_mut() {
+                last.push(')');
+            }
+            tokens.extend(variation);
+            needs_number = true;
+        }
+        position.make_move(node.mv);
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Open,
+    Close,
+    Result(String),
+    Move(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        let at_line_start = line_start;
+        line_start = c == '\n';
+        match c {
+            // escape lines are meant for other software
+            '%' if at_line_start => {
+                chars.by_ref().find(|&c| c == '\n');
+                line_start = true;
+            }
+            c if c.is_whitespace() => {}
+            '[' => tokens.push(read_tag(&mut chars)?),
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => comment.push(c),
+                        None => return Err(PgnError::UnterminatedComment),
+                    }
+                }
+                let words: Vec<&str> = comment.split_whitespace().collect();
+                tokens.push(Token::Comment(words.join(" ")));
+            }
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '\n').collect();
+                tokens.push(Token::Comment(comment.trim().to_string()));
+                line_start = true;
+            }
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '$' => {
+                let mut digits = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                    digits.push(c);
+                    chars.next();
+                }
+                if let Ok(nag) = digits.parse() {
+                    tokens.push(Token::Nag(nag));
+                }
+            }
+            _ => {
+                let mut symbol = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "[]{}();$".contains(c) {
+                        break;
+                    }
+                    symbol.push(c);
+                    chars.next();
+                }
+                push_symbol(&mut tokens, &symbol);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Reads a tag pair after its opening bracket.
+fn read_tag(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Token, PgnError> {
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() || c == '"' || c == ']' {
+            break;
+        }
+        name.push(c);
+        chars.next();
+    }
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    if chars.next() != Some('"') {
+        return Err(PgnError::InvalidTag(name));
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('\\') => match chars.next() {
+                Some(c) => value.push(c),
+                None => return Err(PgnError::InvalidTag(name)),
+            },
+            Some('"') => break,
+            Some(c) => value.push(c),
+            None => return Err(PgnError::InvalidTag(name)),
+        }
+    }
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    if chars.next() != Some(']') || name.is_empty() {
+        return Err(PgnError::InvalidTag(name));
+    }
+    Ok(Token::Tag(name, value))
+}
+
+/// Sorts a bare word of movetext into a result, a move or an annotation,
+/// dropping move numbers.
+fn push_symbol(tokens: &mut Vec<Token>, symbol: &str) {
+    if ["1-0", "0-1", "1/2-1/2", "*"].contains(&symbol) {
+        tokens.push(Token::Result(symbol.to_string()));
+        return;
+    }
+    if symbol.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return;
+    }
+    let mut symbol = symbol;
+    let digits = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+    if digits.len() < symbol.len() && digits.starts_with('.') {
+        symbol = digits.trim_start_matches('.');
+    }
+    let san = symbol.trim_end_matches(['!', '?']);
+    if !san.is_empty() {
+        tokens.push(Token::Move(san.to_string()));
+    }
+    let nag = match &symbol[san.len()..] {
+        "!" => 1,
+        "?" => 2,
+        "!!" => 3,
+        "??" => 4,
+        "!?" => 5,
+        "?!" => 6,
+        _ => return,
+    };
+    tokens.push(Token::Nag(nag));
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn game(&mut self) -> Result<PgnGame, PgnError> {
+        let mut tags = Vec::new();
+        while let Some(Token::Tag(name, value)) = self.peek() {
+            tags.push((name.clone(), value.clone()));
+            self.index += 1;
+        }
+        let start_position = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => Position::from_fen(fen).map_err(PgnError::InvalidFen)?,
+            None => Position::new(),
+        };
+        let moves = self.line(start_position, 0)?;
+        let result = match self.peek() {
+            Some(Token::Result(result)) => {
+                let result = result.clone();
+                self.index += 1;
+                result
+            }
+            _ => tags
+                .iter()
+                .find(|(name, _)| name == "Result")
+                .map_or("*".to_string(), |(_, result)| result.clone()),
+        };
+        Ok(PgnGame {
+            tags,
+            start_position,
+            moves,
+            result,
+        })
+    }
+
+    /// Reads moves played from `position` up to the end of the variation
+    /// or, at `depth` 0, the end of the game.
+    fn line(&mut self, mut position: Position, depth: usize) -> Result<Vec<PgnNode>, PgnError> {
+        let mut nodes: Vec<PgnNode> = Vec::new();
+        let mut before = position;
+        let mut pending_comment: Option<String> = None;
+        loop {
+            let Some(token) = self.peek().cloned() else {
+                if depth > 0 {
+                    return Err(PgnError::UnbalancedParenthesis);
+                }
+                break;
+            };
+            match token {
+                Token::Tag(..) | Token::Result(_) => {
+                    if depth > 0 {
+                        return Err(PgnError::UnbalancedParenthesis);
+                    }
+                    break;
+                }
+                Token::Close => {
+                    self.index += 1;
+                    if depth == 0 {
+                        return Err(PgnError::UnbalancedParenthesis);
+                    }
+                    break;
+                }
+                Token::Open => {
+                    self.index += 1;
+                    let variation = self.line(before, depth + 1)?;
+                    // a variation before any move has nothing to replace
+                    if let Some(last) = nodes.last_mut() {
+                        last.variations.push(variation);
+                    }
+                }
+                Token::Comment(comment) => {
+                    self.index += 1;
+                    let slot = match nodes.last_mut() {
+                        Some(last) => &mut last.comment,
+                        None => &mut pending_comment,
+                    };
+                    *slot = Some(match slot.take() {
+                        Some(earlier) => format!("{} {}", earlier, comment),
+                        None => comment,
+                    });
+                }
+                Token::Nag(nag) => {
+                    self.index += 1;
+                    if let Some(last) = nodes.last_mut() {
+                        last.nags.push(nag);
+                    }
+                }
+                Token::Move(san) => {
+                    self.index += 1;
+                    let mv = ChessMove::parse_san(&position, &san)
+                        .map_err(|error| PgnError::IllegalMove { san, error })?;
+                    before = position;
+                    position.make_move(mv);
+                    let mut node = PgnNode::new(mv);
+                    node.comment_before = pending_comment.take();
+                    nodes.push(node);
+                }
+            }
+        }
+        Ok(nodes)
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+use std::ops::Not;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub const ALL: [Color; 2] = [Color::White, Color::Black];
+
+    /// 0 for white and 1 for black.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl Not for Color {
+    type Output = Color;
+
+    fn not(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Color::White => write!(f, "White"),
+            Color::Black => write!(f, "Black"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl PieceKind {
+    pub const ALL: [PieceKind; 6] = [
+        PieceKind::Pawn,
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Rook,
+        PieceKind::Queen,
+        PieceKind::King,
+    ];
+
+    /// The pieces a pawn may promote to, strongest first.
+    pub const PROMOTIONS: [PieceKind; 4] = [
+        PieceKind::Queen,
+        PieceKind::Rook,
+        PieceKind::Bishop,
+        PieceKind::Knight,
+    ];
+
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Lowercase letter, as used for black pieces in FEN and for
+    /// promotions in coordinate notation.
+    pub const fn to_char(self) -> char {
+        match self {
+            PieceKind::Pawn => 'p',
+            PieceKind::Knight => 'n',
+            PieceKind::Bishop => 'b',
+            PieceKind::Rook => 'r',
+            PieceKind::Queen => 'q',
+            PieceKind::King => 'k',
+        }
+    }
+
+    /// Reads a piece letter in either case.
+    pub fn from_char(c: char) -> Option<PieceKind> {
+        let c = c.to_ascii_lowercase();
+        PieceKind::ALL.into_iter().find(|kind| kind.to_char() == c)
+    }
+}
+
+impl fmt::Display for PieceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            PieceKind::Pawn => "pawn",
+            PieceKind::Knight => "knight",
+            PieceKind::Bishop => "bishop",
+            PieceKind::Rook => "rook",
+            PieceKind::Queen => "queen",
+            PieceKind::King => "king",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A piece of one color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub color: Color,
+    pub kind: PieceKind,
+}
+
+impl Piece {
+    /// Every piece in [`Piece::index`] order.
+    pub const ALL: [Piece; 12] = {
+        let mut pieces = [Piece::new(Color::White, PieceKind::Pawn); 12];
+        let mut i = 0;
+        while i < 12 {
+            pieces[i] = Piece::from_index(i);
+            i += 1;
+        }
+        pieces
+    };
+
+    pub const fn new(color: Color, kind: PieceKind) -> Piece {
+        Piece { color, kind }
+    }
+
+    /// White pawn, knight, bishop, rook, queen and king are 0..6, followed
+    /// by the black pieces in the same order.
+    pub const fn index(self) -> usize {
+        self.color.index() * 6 + self.kind.index()
+    }
+
+    /// The inverse of [`Piece::index`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is 12 or more.
+    pub const fn from_index(index: usize) -> Piece {
+        assert!(index < 12);
+        let color = if index < 6 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        Piece::new(color, PieceKind::ALL[index % 6])
+    }
+
+    /// The FEN letter: uppercase for white, lowercase for black.
+    pub const fn to_char(self) -> char {
+        match self.color {
+            Color::White => self.kind.to_char().to_ascii_uppercase(),
+            Color::Black => self.kind.to_char(),
+        }
+    }
+
+    /// Reads a FEN letter.
+    pub fn from_char(c: char) -> Option<Piece> {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        PieceKind::from_char(c).map(|kind| Piece::new(color, kind))
+    }
+}
+
+/// The FEN letter, such as "N" for a white knight.
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
@@ -0,0 +1,289 @@
+use crate::attacks::pawn_attacks;
+use crate::{
+    Bitboard, CastlingRights, ChessMove, Color, MoveKind, Piece, PieceKind, Square, zobrist,
+};
+
+/// A chess position: piece bitboards, side to move, castling rights, the
+/// en-passant target and the move clocks, plus the Zobrist key of all but
+/// the clocks.
+///
+/// Bitboards are indexed by [`Piece::index`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub(crate) bitboards: [Bitboard; 12],
+    pub(crate) occupancy: [Bitboard; 2],
+    pub(crate) side_to_move: Color,
+    pub(crate) castling_rights: CastlingRights,
+    pub(crate) en_passant: Option<Square>,
+    pub(crate) halfmove_clock: u32,
+    pub(crate) fullmove_number: u32,
+    pub(crate) zobrist_key: u64,
+}
+
+/// What [`Position::make_move`] needs to remember to take a move back. The
+/// captured piece, if any, is part of the move itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    pub mv: ChessMove,
+    pub castling_rights: CastlingRights,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub zobrist_key: u64,
+}
+
+impl Position {
+    /// King from, king to, rook from and rook to squares of each castling
+    /// move, indexed by the number of its [`CastlingRights`] right.
+    pub(crate) const CASTLING_SQUARES: [[Square; 4]; 4] = [
+        [
+            Square::new(4, 0),
+            Square::new(6, 0),
+            Square::new(7, 0),
+            Square::new(5, 0),
+        ],
+        [
+            Square::new(4, 0),
+            Square::new(2, 0),
+            Square::new(0, 0),
+            Square::new(3, 0),
+        ],
+        [
+            Square::new(4, 7),
+            Square::new(6, 7),
+            Square::new(7, 7),
+            Square::new(5, 7),
+        ],
+        [
+            Square::new(4, 7),
+            Square::new(2, 7),
+            Square::new(0, 7),
+            Square::new(3, 7),
+        ],
+    ];
+
+    pub const START_FEN: &'static str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    /// The standard starting position.
+    pub fn new() -> Position {
+        Self::from_fen(Self::START_FEN).expect("the starting position is valid FEN")
+    }
+
+    /// A board with no pieces, white to move and no castling rights.
+    pub(crate) fn empty() -> Position {
+        Position {
+            bitboards: [Bitboard::EMPTY; 12],
+            occupancy: [Bitboard::EMPTY; 2],
+            side_to_move: Color::White,
+            castling_rights: CastlingRights::NONE,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            zobrist_key: 0,
+        }
+    }
+
+    /// The squares of every piece, indexed by [`Piece::index`].
+    pub fn bitboards(&self) -> &[Bitboard; 12] {
+        &self.bitboards
+    }
+
+    pub fn pieces(&self, piece: Piece) -> Bitboard {
+        self.bitboards[piece.index()]
+    }
+
+    /// The squares holding a piece of `color`.
+    pub fn occupancy(&self, color: Color) -> Bitboard {
+        self.occupancy[color.index()]
+    }
+
+    /// The squares holding any piece.
+    pub fn occupied(&self) -> Bitboard {
+        self.occupancy[0] | self.occupancy[1]
+    }
+
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    pub fn king_square(&self, color: Color) -> Square {
+        self.pieces(Piece::new(color, PieceKind::King))
+            .first()
+            .expect("each side has a king")
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    /// The square a pawn skipped with a double push on the last ply.
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        Piece::ALL
+            .into_iter()
+            .find(|piece| self.pieces(*piece).contains(square))
+    }
+
+    /// Plays `mv` for the side to move and returns the record that
+    /// [`Position::unmake_move`] takes to restore the position. The move is
+    /// not validated; it should come from [`Position::generate_moves`].
+    pub fn make_move(&mut self, mv: ChessMove) -> Undo {
+        let undo = Undo {
+            mv,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            zobrist_key: self.zobrist_key,
+        };
+        self.zobrist_key ^= self.en_passant_key() ^ zobrist::castling(self.castling_rights);
+        self.zobrist_key ^= Self::move_key(mv);
+        Self::move_pieces(&mut self.bitboards, mv);
+        self.update_occupancy();
+        // moving the king or a rook, or capturing a rook on its home square,
+        // gives up the matching rights
+        let touched = mv.from_square.bitboard() | mv.to_square.bitboard();
+        for (i, squares) in Self::CASTLING_SQUARES.iter().enumerate() {
+            if !(touched & (squares[0].bitboard() | squares[2].bitboard())).is_empty() {
+                self.castling_rights.remove(CastlingRights::from_index(i));
+            }
+        }
+        self.en_passant = if mv.kind == MoveKind::DoublePawnPush {
+            let rank = (mv.from_square.rank() + mv.to_square.rank()) / 2;
+            Some(Square::new(mv.from_square.file(), rank))
+        } else {
+            None
+        };
+        if mv.is_capture() || mv.piece.kind == PieceKind::Pawn {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.side_to_move == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = !self.side_to_move;
+        self.zobrist_key ^= zobrist::white_to_move();
+        self.zobrist_key ^= self.en_passant_key() ^ zobrist::castling(self.castling_rights);
+        debug_assert_eq!(
+            self.zobrist_key,
+            self.compute_zobrist_key(),
+            "incremental Zobrist key out of sync after {}",
+            mv
+        );
+        undo
+    }
+
+    /// Takes back the move recorded in `undo`, which must be the last move
+    /// made on this position.
+    pub fn unmake_move(&mut self, undo: Undo) {
+        self.side_to_move = !self.side_to_move;
+        if self.side_to_move == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        // move_pieces only toggles bits, so playing the move again undoes it
+        Self::move_pieces(&mut self.bitboards, undo.mv);
+        self.update_occupancy();
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.zobrist_key = undo.zobrist_key;
+    }
+
+    /// The 64-bit Zobrist key of the pieces, side to move, castling rights
+    /// and en-passant file. See the `zobrist` module for the key layout.
+    pub fn zobrist_key(&self) -> u64 {
+        self.zobrist_key
+    }
+
+    /// The Zobrist key computed from scratch, which `make_move` checks the
+    /// incremental key against in debug builds.
+    pub(crate) fn compute_zobrist_key(&self) -> u64 {
+        let mut key = zobrist::castling(self.castling_rights) ^ self.en_passant_key();
+        for piece in Piece::ALL {
+            for square in self.pieces(piece) {
+                key ^= zobrist::piece(piece, square);
+            }
+        }
+        if self.side_to_move == Color::White {
+            key ^= zobrist::white_to_move();
+        }
+        key
+    }
+
+    /// The en-passant part of the Zobrist key. Like Polyglot, the file only
+    /// counts when a pawn of the side to move could capture onto it.
+    pub(crate) fn en_passant_key(&self) -> u64 {
+        let Some(square) = self.en_passant else {
+            return 0;
+        };
+        let pawns = self.pieces(Piece::new(self.side_to_move, PieceKind::Pawn));
+        if (pawn_attacks(square, !self.side_to_move) & pawns).is_empty() {
+            0
+        } else {
+            zobrist::en_passant(square)
+        }
+    }
+
+    /// The change [`Position::move_pieces`] makes to the Zobrist key.
+    fn move_key(mv: ChessMove) -> u64 {
+        let mut key = zobrist::piece(mv.piece, mv.from_square);
+        let arrived = match mv.promotion {
+            Some(kind) => Piece::new(mv.piece.color, kind),
+            None => mv.piece,
+        };
+        key ^= zobrist::piece(arrived, mv.to_square);
+        if let Some(captured) = mv.capture {
+            key ^= zobrist::piece(captured, mv.captured_square());
+        }
+        if let Some(index) = mv.castling_index() {
+            let squares = Self::CASTLING_SQUARES[index];
+            let rook = Piece::new(mv.piece.color, PieceKind::Rook);
+            key ^= zobrist::piece(rook, squares[2]) ^ zobrist::piece(rook, squares[3]);
+        }
+        key
+    }
+
+    /// Moves the pieces of `mv` on `bitboards`, leaving the rest of the
+    /// position state alone.
+    pub(crate) fn move_pieces(bitboards: &mut [Bitboard; 12], mv: ChessMove) {
+        if let Some(captured) = mv.capture {
+            bitboards[captured.index()] ^= mv.captured_square().bitboard();
+        }
+        bitboards[mv.piece.index()] ^= mv.from_square.bitboard() | mv.to_square.bitboard();
+        if let Some(promotion) = mv.promotion {
+            bitboards[mv.piece.index()] ^= mv.to_square.bitboard();
+            bitboards[Piece::new(mv.piece.color, promotion).index()] ^= mv.to_square.bitboard();
+        }
+        if let Some(index) = mv.castling_index() {
+            let squares = Self::CASTLING_SQUARES[index];
+            let rook = Piece::new(mv.piece.color, PieceKind::Rook);
+            bitboards[rook.index()] ^= squares[2].bitboard() | squares[3].bitboard();
+        }
+    }
+
+    pub(crate) fn update_occupancy(&mut self) {
+        for color in Color::ALL {
+            self.occupancy[color.index()] = PieceKind::ALL
+                .into_iter()
+                .fold(Bitboard::EMPTY, |acc, kind| {
+                    acc | self.bitboards[Piece::new(color, kind).index()]
+                });
+        }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
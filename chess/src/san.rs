@@ -0,0 +1,170 @@
+use crate::{ChessMove, MoveKind, PieceKind, Position, Square};
+use std::fmt;
+
+/// Why a move in standard algebraic notation could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    /// The text is not shaped like a SAN move.
+    InvalidSyntax(String),
+    /// No legal move matches.
+    IllegalMove(String),
+    /// More than one legal move matches.
+    AmbiguousMove(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanError::InvalidSyntax(san) => write!(f, "'{}' is not a SAN move", san),
+            SanError::IllegalMove(san) => write!(f, "'{}' is not a legal move", san),
+            SanError::AmbiguousMove(san) => write!(f, "'{}' matches more than one move", san),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+impl ChessMove {
+    /// Writes the move in standard algebraic notation, such as "Nbd7",
+    /// "exd6", "O-O-O", "e8=Q+" or "Qxf7#". The move must be legal in
+    /// `position`.
+    pub fn to_san(&self, position: &Position) -> String {
+        let mut san = String::new();
+        if self.kind == MoveKind::KingsideCastle {
+            san.push_str("O-O");
+        } else if self.kind == MoveKind::QueensideCastle {
+            san.push_str("O-O-O");
+        } else {
+            if self.piece.kind == PieceKind::Pawn {
+                if self.is_capture() {
+                    san.push((b'a' + self.from_square.file()) as char);
+                }
+            } else {
+                san.push(self.piece.kind.to_char().to_ascii_uppercase());
+                san.push_str(&self.disambiguation(position));
+            }
+            if self.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&self.to_square.to_string());
+            if let Some(promotion) = self.promotion {
+                san.push('=');
+                san.push(promotion.to_char().to_ascii_uppercase());
+            }
+        }
+
+        let mut after = *position;
+        after.make_move(*self);
+        if after.in_check() {
+            san.push(if after.generate_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+        san
+    }
+
+    /// The file, rank or square of the origin that tells this move apart
+    /// from other moves of the same kind of piece to the same square.
+    fn disambiguation(&self, position: &Position) -> String {
+        let rivals: Vec<Square> = position
+            .generate_moves()
+            .iter()
+            .filter(|mv| {
+                mv.piece == self.piece
+                    && mv.to_square == self.to_square
+                    && mv.from_square != self.from_square
+            })
+            .map(|mv| mv.from_square)
+            .collect();
+        let file = (b'a' + self.from_square.file()) as char;
+        let rank = (b'1' + self.from_square.rank()) as char;
+        if rivals.is_empty() {
+            String::new()
+        } else if rivals
+            .iter()
+            .all(|square| square.file() != self.from_square.file())
+        {
+            file.to_string()
+        } else if rivals
+            .iter()
+            .all(|square| square.rank() != self.from_square.rank())
+        {
+            rank.to_string()
+        } else {
+            format!("{}{}", file, rank)
+        }
+    }
+
+    /// Reads a move in standard algebraic notation and resolves it to the
+    /// one legal move in `position` it describes.
+    ///
+    /// Check, mate and annotation suffixes such as "+", "#" and "!?" are
+    /// ignored, castling may be written with zeros, and the "=" before a
+    /// promotion piece may be left out.
+    pub fn parse_san(position: &Position, san: &str) -> Result<ChessMove, SanError> {
+        let invalid = || SanError::InvalidSyntax(san.to_string());
+        let text = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        let moves = position.generate_moves();
+
+        let candidates: Vec<ChessMove> = match text {
+            "O-O" | "0-0" => moves
+                .into_iter()
+                .filter(|mv| mv.kind == MoveKind::KingsideCastle)
+                .collect(),
+            "O-O-O" | "0-0-0" => moves
+                .into_iter()
+                .filter(|mv| mv.kind == MoveKind::QueensideCastle)
+                .collect(),
+            _ => {
+                let mut rest = text;
+                let kind = match rest.chars().next() {
+                    Some(c @ ('N' | 'B' | 'R' | 'Q' | 'K')) => {
+                        rest = &rest[1..];
+                        PieceKind::from_char(c).ok_or_else(invalid)?
+                    }
+                    _ => PieceKind::Pawn,
+                };
+                let mut promotion = None;
+                if let Some(c @ ('N' | 'B' | 'R' | 'Q')) = rest.chars().last() {
+                    promotion = PieceKind::from_char(c);
+                    rest = rest[..rest.len() - 1].trim_end_matches('=');
+                }
+                if rest.len() < 2 || !rest.is_char_boundary(rest.len() - 2) {
+                    return Err(invalid());
+                }
+                let (origin, target) = rest.split_at(rest.len() - 2);
+                let to_square = Square::from_algebraic(target).ok_or_else(invalid)?;
+                let origin = origin.strip_suffix('x').unwrap_or(origin);
+                let mut from_file = None;
+                let mut from_rank = None;
+                for c in origin.chars() {
+                    match c {
+                        'a'..='h' if from_file.is_none() && from_rank.is_none() => {
+                            from_file = Some(c as u8 - b'a');
+                        }
+                        '1'..='8' if from_rank.is_none() => from_rank = Some(c as u8 - b'1'),
+                        _ => return Err(invalid()),
+                    }
+                }
+                moves
+                    .into_iter()
+                    .filter(|mv| {
+                        mv.piece.kind == kind
+                            && mv.to_square == to_square
+                            && mv.promotion == promotion
+                            && from_file.is_none_or(|file| mv.from_square.file() == file)
+                            && from_rank.is_none_or(|rank| mv.from_square.rank() == rank)
+                    })
+                    .collect()
+            }
+        };
+
+        match candidates[..] {
+            [mv] => Ok(mv),
+            [] => Err(SanError::IllegalMove(san.to_string())),
+            _ => Err(SanError::AmbiguousMove(san.to_string())),
+        }
+    }
+}
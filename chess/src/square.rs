@@ -0,0 +1,66 @@
+use crate::Bitboard;
+use std::fmt;
+
+/// One of the 64 squares of the board.
+///
+/// The square's index is the bit it occupies in a [`Bitboard`], in
+/// little-endian rank-file order: a1 is 0, b1 is 1, h1 is 7, a2 is 8 and h8
+/// is 63. Files and ranks count from 0, so a1 is file 0, rank 0.
+///
+/// Earlier versions of the engine numbered the files of each rank from h to
+/// a instead (h1 was 0 and a1 was 7). An index from that layout converts to
+/// this one with `index ^ 7`, which flips the file and keeps the rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square(u8);
+
+impl Square {
+    /// The square on `file` (0 is the a-file) and `rank` (0 is the first
+    /// rank).
+    pub const fn new(file: u8, rank: u8) -> Square {
+        assert!(file < 8 && rank < 8);
+        Square(rank * 8 + file)
+    }
+
+    /// The square occupying bit `index` of a bitboard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is 64 or more.
+    pub const fn from_index(index: usize) -> Square {
+        assert!(index < 64);
+        Square(index as u8)
+    }
+
+    /// Parses an algebraic square name such as "e4".
+    pub fn from_algebraic(name: &str) -> Option<Square> {
+        match name.as_bytes() {
+            &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                Some(Square::new(file - b'a', rank - b'1'))
+            }
+            _ => None,
+        }
+    }
+
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub const fn file(self) -> u8 {
+        self.0 % 8
+    }
+
+    pub const fn rank(self) -> u8 {
+        self.0 / 8
+    }
+
+    pub const fn bitboard(self) -> Bitboard {
+        Bitboard(1 << self.0)
+    }
+}
+
+/// Algebraic name, such as "e4".
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file()) as char, self.rank() + 1)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{ChessMove, PieceKind, Position, Square};
+use std::fmt;
+
+/// Why a move in UCI notation could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciError {
+    /// The text is not two squares and an optional promotion letter.
+    InvalidSyntax(String),
+    /// No legal move matches.
+    IllegalMove(String),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciError::InvalidSyntax(uci) => write!(f, "'{}' is not a UCI move", uci),
+            UciError::IllegalMove(uci) => write!(f, "'{}' is not a legal move", uci),
+        }
+    }
+}
+
+impl std::error::Error for UciError {}
+
+impl ChessMove {
+    /// Writes the move in UCI long algebraic notation, such as "e2e4" or
+    /// "e7e8q". Castling is written as the king's two-square move, or as the
+    /// king taking its own rook when `king_takes_rook` is set, which is how
+    /// Chess960 GUIs expect it.
+    pub fn to_uci(&self, king_takes_rook: bool) -> String {
+        match self.castling_index() {
+            Some(index) if king_takes_rook => {
+                let rook_from = Position::CASTLING_SQUARES[index][2];
+                format!("{}{}", self.from_square, rook_from)
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// Reads a move in UCI notation and resolves it to the legal move in
+    /// `position` it describes. Castling is accepted in both encodings.
+    pub fn parse_uci(position: &Position, uci: &str) -> Result<ChessMove, UciError> {
+        let invalid = || UciError::InvalidSyntax(uci.to_string());
+        let text = uci.trim();
+        if !text.is_ascii() || !(4..=5).contains(&text.len()) {
+            return Err(invalid());
+        }
+        let from_square = Square::from_algebraic(&text[..2]).ok_or_else(invalid)?;
+        let to_square = Square::from_algebraic(&text[2..4]).ok_or_else(invalid)?;
+        let promotion = match text[4..].chars().next() {
+            Some(c @ ('n' | 'b' | 'r' | 'q')) => PieceKind::from_char(c),
+            Some(_) => return Err(invalid()),
+            None => None,
+        };
+        position
+            .generate_moves()
+            .into_iter()
+            .find(|mv| {
+                mv.from_square == from_square
+                    && mv.promotion == promotion
+                    && (mv.to_square == to_square
+                        || mv
+                            .castling_index()
+                            .is_some_and(|i| Position::CASTLING_SQUARES[i][2] == to_square))
+            })
+            .ok_or_else(|| UciError::IllegalMove(uci.to_string()))
+    }
+}
@@ -0,0 +1,57 @@
+use chess::attacks::{
+    between, bishop_attacks, king_attacks, knight_attacks, line, pawn_attacks, queen_attacks,
+    rook_attacks,
+};
+use chess::{Bitboard, Color, Square};
+
+fn squares(names: &[&str]) -> Bitboard {
+    names
+        .iter()
+        .fold(Bitboard::EMPTY, |acc, name| acc | sq(name).bitboard())
+}
+
+fn sq(name: &str) -> Square {
+    Square::from_algebraic(name).unwrap()
+}
+
+#[test]
+fn sliders_stop_at_blockers() {
+    let occupied = squares(&["d6", "f4", "b2"]);
+    assert_eq!(
+        rook_attacks(sq("d4"), occupied),
+        squares(&["d5", "d6", "d3", "d2", "d1", "e4", "f4", "c4", "b4", "a4"])
+    );
+    assert_eq!(
+        bishop_attacks(sq("d4"), occupied),
+        squares(&[
+            "e5", "f6", "g7", "h8", "c5", "b6", "a7", "e3", "f2", "g1", "c3", "b2"
+        ])
+    );
+    assert_eq!(
+        queen_attacks(sq("a1"), Bitboard::EMPTY).count(),
+        21,
+        "a queen in the corner of an empty board"
+    );
+}
+
+#[test]
+fn between_and_line() {
+    assert_eq!(between(sq("a1"), sq("d4")), squares(&["b2", "c3"]));
+    assert_eq!(between(sq("e1"), sq("h1")), squares(&["f1", "g1"]));
+    assert!(between(sq("e1"), sq("f3")).is_empty());
+    assert_eq!(line(sq("c3"), sq("d4")).count(), 8);
+    assert_eq!(
+        line(sq("b1"), sq("b5")),
+        squares(&["b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8"])
+    );
+    assert!(line(sq("e1"), sq("f3")).is_empty());
+}
+
+#[test]
+fn leapers_do_not_wrap_around_the_board() {
+    assert_eq!(knight_attacks(sq("a1")), squares(&["b3", "c2"]));
+    assert_eq!(knight_attacks(sq("h5")), squares(&["g7", "f6", "f4", "g3"]));
+    assert_eq!(king_attacks(sq("h1")), squares(&["g1", "g2", "h2"]));
+    assert_eq!(pawn_attacks(sq("a2"), Color::White), squares(&["b3"]));
+    assert_eq!(pawn_attacks(sq("h7"), Color::Black), squares(&["g6"]));
+}
@@ -0,0 +1,84 @@
+use chess::{FenError, Position};
+
+#[test]
+fn round_trips() {
+    for fen in [
+        Position::START_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 12 40",
+    ] {
+        assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn clocks_are_optional() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K3 b -  -").unwrap();
+    assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+}
+
+#[test]
+fn malformed_input_has_precise_errors() {
+    let cases = [
+        ("4k3/8/8/8/8/8/8/4K3 w -", FenError::WrongFieldCount(3)),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0", FenError::WrongFieldCount(5)),
+        ("4k3/8/8/8/8/8/4K3 w - - 0 1", FenError::WrongRankCount(7)),
+        (
+            "4k3/8/8/8/8/8/8/4K4 w - - 0 1",
+            FenError::WrongRankLength(1),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K2 w - - 0 1",
+            FenError::WrongRankLength(1),
+        ),
+        ("4k3/8/8/8/8/8/8/4X3 w - - 0 1", FenError::InvalidPiece('X')),
+        ("8/8/8/8/8/8/8/4K3 w - - 0 1", FenError::InvalidKingCount),
+        ("4k3/8/8/8/8/8/8/3KK3 w - - 0 1", FenError::InvalidKingCount),
+        ("4k2P/8/8/8/8/8/8/4K3 w - - 0 1", FenError::PawnOnBackRank),
+        ("4k3/8/8/8/8/8/8/p3K3 w - - 0 1", FenError::PawnOnBackRank),
+        ("4k3/8/8/8/8/8/4R3/4K3 w - - 0 1", FenError::OpponentInCheck),
+        (
+            "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+            FenError::InvalidSideToMove("x".into()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w KK - 0 1",
+            FenError::InvalidCastling("KK".into()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w X - 0 1",
+            FenError::InvalidCastling("X".into()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - e3 0 1",
+            FenError::InvalidEnPassant("e3".into()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - z6 0 1",
+            FenError::InvalidEnPassant("z6".into()),
+        ),
+        // no black pawn has just passed e6
+        (
+            "4k3/8/8/3P4/8/8/8/4K3 w - e6 0 1",
+            FenError::InvalidEnPassant("e6".into()),
+        ),
+        // the pawn is there, but so is something on its start square
+        (
+            "4k3/4n3/8/3Pp3/8/8/8/4K3 w - e6 0 1",
+            FenError::InvalidEnPassant("e6".into()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+            FenError::InvalidHalfmoveClock("x".into()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+            FenError::InvalidFullmoveNumber("0".into()),
+        ),
+    ];
+    for (fen, error) in cases {
+        assert_eq!(Position::from_fen(fen), Err(error), "{}", fen);
+    }
+}
@@ -0,0 +1,82 @@
+use chess::{Color, DrawReason, Game, GameStatus, Square};
+
+fn play(game: &mut Game, moves: &[&str]) {
+    for mv in moves {
+        let from = Square::from_algebraic(&mv[..2]).unwrap();
+        let to = Square::from_algebraic(&mv[2..]).unwrap();
+        let mv = game.position().find_move(from, to).expect(mv);
+        game.make_move(mv);
+    }
+}
+
+#[test]
+fn fools_mate() {
+    let mut game = Game::new();
+    play(&mut game, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert_eq!(
+        game.status(),
+        GameStatus::Checkmate {
+            winner: Color::Black
+        }
+    );
+    assert_eq!(game.status().result(), "0-1");
+}
+
+#[test]
+fn threefold_is_claimable_and_fivefold_ends_the_game() {
+    let mut game = Game::new();
+    let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    play(&mut game, &shuffle);
+    assert_eq!(game.claimable_draw(), None);
+    play(&mut game, &shuffle);
+    assert_eq!(game.claimable_draw(), Some(DrawReason::ThreefoldRepetition));
+    play(&mut game, &shuffle);
+    play(&mut game, &shuffle);
+    assert_eq!(
+        game.status(),
+        GameStatus::Draw(DrawReason::FivefoldRepetition)
+    );
+}
+
+#[test]
+fn undo_and_redo() {
+    let mut game = Game::new();
+    play(&mut game, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    let mated = game.position().to_fen();
+    assert!(game.undo());
+    assert_eq!(game.status(), GameStatus::Ongoing);
+    assert_eq!(
+        game.last_move().map(|mv| mv.to_string()),
+        Some("g2g4".into())
+    );
+    assert!(game.undo());
+    assert!(game.redo());
+    assert!(game.redo());
+    assert!(!game.redo());
+    assert_eq!(
+        game.last_move().map(|mv| mv.to_string()),
+        Some("d8h4".into())
+    );
+    assert_eq!(game.position().to_fen(), mated);
+    assert!(game.status().is_over());
+}
+
+#[test]
+fn redo_does_not_reopen_a_finished_game() {
+    let mut game = Game::new();
+    let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    play(&mut game, &shuffle);
+    play(&mut game, &shuffle);
+    play(&mut game, &shuffle[..2]);
+    assert!(game.undo());
+    assert!(game.claim_draw());
+    assert!(!game.redo());
+    assert_eq!(
+        game.status(),
+        GameStatus::Draw(DrawReason::ThreefoldRepetition)
+    );
+    assert_eq!(
+        game.last_move().map(|mv| mv.to_string()),
+        Some("g1f3".into())
+    );
+}
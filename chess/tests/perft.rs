@@ -0,0 +1,70 @@
+//! Node counts for the standard perft positions from
+//! <https://www.chessprogramming.org/Perft_Results>.
+
+use chess::Position;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_4_MIRRORED: &str =
+    "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+fn assert_perft(fen: &str, counts: &[u64]) {
+    let position = Position::from_fen(fen).unwrap();
+    for (depth, &count) in counts.iter().enumerate() {
+        let depth = depth as u32 + 1;
+        assert_eq!(position.perft(depth), count, "{} at depth {}", fen, depth);
+    }
+}
+
+#[test]
+fn start_position() {
+    assert_perft(Position::START_FEN, &[20, 400, 8902, 197281]);
+}
+
+#[test]
+fn kiwipete() {
+    assert_perft(KIWIPETE, &[48, 2039, 97862]);
+}
+
+#[test]
+fn position_3() {
+    assert_perft(POSITION_3, &[14, 191, 2812, 43238]);
+}
+
+#[test]
+fn position_4() {
+    assert_perft(POSITION_4, &[6, 264, 9467]);
+    assert_perft(POSITION_4_MIRRORED, &[6, 264, 9467]);
+}
+
+#[test]
+fn position_5() {
+    assert_perft(POSITION_5, &[44, 1486, 62379]);
+}
+
+#[test]
+fn position_6() {
+    assert_perft(POSITION_6, &[46, 2079, 89890]);
+}
+
+#[test]
+fn divide_adds_up_to_perft() {
+    let position = Position::from_fen(KIWIPETE).unwrap();
+    let divide = position.perft_divide(2);
+    assert_eq!(divide.len(), 48);
+    assert_eq!(divide.iter().map(|(_, count)| count).sum::<u64>(), 2039);
+}
+
+#[test]
+#[ignore = "slow in debug builds; run with --release -- --ignored"]
+fn deep() {
+    assert_perft(Position::START_FEN, &[20, 400, 8902, 197281, 4865609]);
+    assert_perft(KIWIPETE, &[48, 2039, 97862, 4085603]);
+    assert_perft(POSITION_3, &[14, 191, 2812, 43238, 674624]);
+    assert_perft(POSITION_4, &[6, 264, 9467, 422333]);
+    assert_perft(POSITION_5, &[44, 1486, 62379, 2103487]);
+    assert_perft(POSITION_6, &[46, 2079, 89890, 3894594]);
+}
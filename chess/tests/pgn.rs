@@ -0,0 +1,190 @@
+use chess::{Game, GameStatus, PgnError, PgnGame, Position, Square};
+
+fn play(game: &mut Game, moves: &[&str]) {
+    for mv in moves {
+        let from = Square::from_algebraic(&mv[..2]).unwrap();
+        let to = Square::from_algebraic(&mv[2..]).unwrap();
+        let mv = game.position().find_move(from, to).expect(mv);
+        game.make_move(mv);
+    }
+}
+
+#[test]
+fn writes_a_finished_game() {
+    let mut game = Game::new();
+    play(
+        &mut game,
+        &["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"],
+    );
+    let mut pgn = PgnGame::from_game(&game);
+    pgn.set_tag("White", "Doe, \"Lucky\" J.");
+    pgn.set_tag("Annotator", "club");
+    assert_eq!(
+        pgn.to_pgn(),
+        "[Event \"?\"]\n\
+         [Site \"?\"]\n\
+         [Date \"????.??.??\"]\n\
+         [Round \"?\"]\n\
+         [White \"Doe, \\\"Lucky\\\" J.\"]\n\
+         [Black \"?\"]\n\
+         [Result \"1-0\"]\n\
+         [Annotator \"club\"]\n\
+         \n\
+         1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"
+    );
+    let read = PgnGame::parse(&pgn.to_pgn()).unwrap();
+    assert_eq!(read, pgn);
+    assert_eq!(
+        read.to_game().status(),
+        GameStatus::Checkmate {
+            winner: chess::Color::White
+        }
+    );
+}
+
+#[test]
+fn wraps_long_movetext() {
+    let mut game = Game::new();
+    let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    for _ in 0..4 {
+        play(&mut game, &shuffle);
+    }
+    let text = PgnGame::from_game(&game).to_pgn();
+    assert!(text.lines().all(|line| line.len() < 80));
+    let movetext = text.split("\n\n").nth(1).unwrap();
+    assert!(movetext.lines().count() > 1);
+    assert_eq!(PgnGame::parse(&text).unwrap().moves.len(), 16);
+}
+
+#[test]
+fn reads_annotated_games() {
+    let text = r#"
+% produced by some other program
+[Event "Casual \"blitz\""]
+[White "A"]
+[Black "B"]
+
+{Opening remarks} 1. e4 e5 2. Nf3!? ; the main move
+Nc6 (2... d6 {Philidor} 3. d4 (3. Bc4 Be7) exd4) (2... Nf6 $1) 3. Bb5 a6 $2
+4. Ba4 *
+
+[Event "Second"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1"]
+
+1. O-O-O 1/2-1/2
+"#;
+    let games = PgnGame::parse_all(text).unwrap();
+    assert_eq!(games.len(), 2);
+
+    let first = &games[0];
+    assert_eq!(first.tag("Event"), Some("Casual \"blitz\""));
+    assert_eq!(first.result, "*");
+    assert_eq!(first.moves.len(), 7);
+    assert_eq!(
+        first.moves[0].comment_before.as_deref(),
+        Some("Opening remarks")
+    );
+    assert_eq!(first.moves[2].nags, vec![5]);
+    assert_eq!(first.moves[2].comment.as_deref(), Some("the main move"));
+    let alternatives = &first.moves[3].variations;
+    assert_eq!(alternatives.len(), 2);
+    assert_eq!(alternatives[0].len(), 3);
+    assert_eq!(alternatives[0][0].comment.as_deref(), Some("Philidor"));
+    assert_eq!(alternatives[0][1].variations[0].len(), 2);
+    assert_eq!(alternatives[1][0].nags, vec![1]);
+    assert_eq!(first.moves[5].nags, vec![2]);
+
+    let second = &games[1];
+    assert_eq!(second.result, "1/2-1/2");
+    assert!(second.moves[0].mv.is_castling());
+    assert_ne!(second.start_position, Position::new());
+
+    // writing and reading back keeps the whole tree; the roster tags are
+    // filled in on the way
+    for game in &games {
+        let read = PgnGame::parse(&game.to_pgn()).unwrap();
+        assert_eq!(read.moves, game.moves);
+        assert_eq!(read.start_position, game.start_position);
+        assert_eq!(read.result, game.result);
+        assert_eq!(read.tag("Event"), game.tag("Event"));
+        assert_eq!(read.tag("Site"), Some("?"));
+    }
+}
+
+#[test]
+fn reports_broken_games() {
+    assert!(matches!(
+        PgnGame::parse("1. e4 e5 2. Ke3"),
+        Err(PgnError::IllegalMove { .. })
+    ));
+    assert_eq!(
+        PgnGame::parse("1. e4 (1. d4"),
+        Err(PgnError::UnbalancedParenthesis)
+    );
+    assert_eq!(
+        PgnGame::parse("1. e4 {never closed"),
+        Err(PgnError::UnterminatedComment)
+    );
+    assert!(matches!(
+        PgnGame::parse("[Event \"x]"),
+        Err(PgnError::InvalidTag(_))
+    ));
+    assert_eq!(PgnGame::parse("  \n"), Err(PgnError::NoGame));
+}
+
+#[test]
+fn results_carry_over_into_the_game() {
+    let status = |text: &str| PgnGame::parse(text).unwrap().to_game().status();
+    assert_eq!(
+        status("1. e4 e5 2. Nf3 1-0"),
+        GameStatus::Resignation {
+            winner: chess::Color::White
+        }
+    );
+    assert_eq!(
+        status("[Termination \"time forfeit\"]\n\n1. e4 0-1"),
+        GameStatus::Timeout {
+            winner: chess::Color::Black
+        }
+    );
+    assert_eq!(
+        status("1. d4 d5 1/2-1/2"),
+        GameStatus::Draw(chess::DrawReason::Agreement)
+    );
+    assert_eq!(status("1. d4 d5 *"), GameStatus::Ongoing);
+
+    let resigned = PgnGame::parse("1. e4 e5 0-1").unwrap().to_game();
+    assert_eq!(PgnGame::from_game(&resigned).result, "0-1");
+}
+
+#[test]
+fn saving_a_loaded_game_keeps_its_record() {
+    let loaded = PgnGame::parse(
+        "[Event \"Club ch\"]\n[White \"Ann\"]\n[Annotator \"Bo\"]\n\n1. e4 {good} e5 (1... c5) *",
+    )
+    .unwrap();
+    let mut game = loaded.to_game();
+    play(&mut game, &["g1f3"]);
+    let saved = loaded.with_game(&game);
+    assert_eq!(saved.tag("Event"), Some("Club ch"));
+    assert_eq!(saved.tag("White"), Some("Ann"));
+    assert_eq!(saved.tag("Annotator"), Some("Bo"));
+    assert_eq!(saved.tag("Black"), Some("?"));
+    assert_eq!(saved.moves.len(), 3);
+    assert_eq!(saved.moves[0].comment.as_deref(), Some("good"));
+    assert_eq!(saved.moves[1].variations.len(), 1);
+    assert!(
+        saved
+            .to_pgn()
+            .contains("1. e4 {good} 1... e5 (1... c5) 2. Nf3 *")
+    );
+
+    // a move taken back and replaced drops what was written about it
+    game.undo();
+    game.undo();
+    play(&mut game, &["d7d5"]);
+    let saved = loaded.with_game(&game);
+    assert_eq!(saved.moves[0].comment.as_deref(), Some("good"));
+    assert!(saved.moves[1].variations.is_empty());
+}
@@ -0,0 +1,91 @@
+use chess::{Position, Square};
+
+#[test]
+fn unmake_restores_every_move() {
+    for fen in [
+        Position::START_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ] {
+        let mut position = Position::from_fen(fen).unwrap();
+        for mv in position.generate_moves() {
+            let undo = position.make_move(mv);
+            position.unmake_move(undo);
+            assert_eq!(position.to_fen(), fen, "after {}", mv);
+            assert_eq!(position, Position::from_fen(fen).unwrap(), "after {}", mv);
+        }
+    }
+}
+
+fn play(position: &mut Position, moves: &[&str]) {
+    for mv in moves {
+        let from = Square::from_algebraic(&mv[..2]).unwrap();
+        let to = Square::from_algebraic(&mv[2..]).unwrap();
+        let mv = position.find_move(from, to).expect(mv);
+        position.make_move(mv);
+    }
+}
+
+#[test]
+fn zobrist_keys_follow_the_position_not_the_move_order() {
+    let mut a = Position::new();
+    let mut b = Position::new();
+    play(&mut a, &["g1f3", "g8f6", "b1c3", "b8c6"]);
+    play(&mut b, &["b1c3", "b8c6", "g1f3", "g8f6"]);
+    assert_eq!(a.zobrist_key(), b.zobrist_key());
+    assert_eq!(
+        a.zobrist_key(),
+        Position::from_fen(&a.to_fen()).unwrap().zobrist_key()
+    );
+
+    let start = Position::new();
+    let black_to_move = Position::from_fen(&start.to_fen().replace(" w ", " b ")).unwrap();
+    assert_ne!(start.zobrist_key(), black_to_move.zobrist_key());
+    let no_castling = Position::from_fen(&start.to_fen().replace("KQkq", "Kkq")).unwrap();
+    assert_ne!(start.zobrist_key(), no_castling.zobrist_key());
+}
+
+#[test]
+fn en_passant_file_only_counts_when_a_capture_is_possible() {
+    let with = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    let without = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3";
+    let key = |fen| Position::from_fen(fen).unwrap().zobrist_key();
+    assert_ne!(key(with), key(without));
+    let unused = "rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w KQkq e6 0 2";
+    let plain = "rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 2";
+    assert_eq!(key(unused), key(plain));
+}
+
+#[test]
+fn zobrist_keys_match_polyglot() {
+    // the test positions from the Polyglot book format description
+    let cases: [(&[&str], u64); 9] = [
+        (&[], 0x463b96181691fc9c),
+        (&["e2e4"], 0x823c9b50fd114196),
+        (&["e2e4", "d7d5"], 0x0756b94461c50fb0),
+        (&["e2e4", "d7d5", "e4e5"], 0x662fafb965db29d4),
+        (&["e2e4", "d7d5", "e4e5", "f7f5"], 0x22a48b5a8e47ff78),
+        (
+            &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"],
+            0x652a607ca3f242c1,
+        ),
+        (
+            &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"],
+            0x00fdd303c946bdd9,
+        ),
+        (
+            &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"],
+            0x3c8123ea7b067637,
+        ),
+        (
+            &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"],
+            0x5c3f9b829b279560,
+        ),
+    ];
+    for (moves, key) in cases {
+        let mut position = Position::new();
+        play(&mut position, moves);
+        assert_eq!(position.zobrist_key(), key, "after {:?}", moves);
+    }
+}
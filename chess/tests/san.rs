@@ -0,0 +1,95 @@
+use chess::{ChessMove, Position, SanError, Square};
+
+fn san_of(fen: &str, from: &str, to: &str) -> String {
+    let position = Position::from_fen(fen).unwrap();
+    let from = Square::from_algebraic(from).unwrap();
+    let to = Square::from_algebraic(to).unwrap();
+    let mv = position
+        .generate_moves()
+        .into_iter()
+        .find(|mv| mv.from_square == from && mv.to_square == to)
+        .expect("a legal move");
+    mv.to_san(&position)
+}
+
+#[test]
+fn formats_moves() {
+    let knights = "rnbqkb1r/ppp2ppp/5n2/3pp3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 0 3";
+    assert_eq!(san_of(knights, "b8", "d7"), "Nbd7");
+    assert_eq!(san_of(knights, "f6", "d7"), "Nfd7");
+    assert_eq!(san_of(knights, "d5", "e4"), "dxe4");
+    let en_passant = "rnbqkbnr/ppp2ppp/4p3/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+    assert_eq!(san_of(en_passant, "e5", "d6"), "exd6");
+    let castling = "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1";
+    assert_eq!(san_of(castling, "e1", "c1"), "O-O-O");
+    assert_eq!(san_of(castling, "e1", "g1"), "O-O");
+    assert_eq!(
+        san_of("7k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7", "e8"),
+        "e8=Q+"
+    );
+    let scholars = "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+    assert_eq!(san_of(scholars, "h5", "f7"), "Qxf7#");
+    let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+    assert_eq!(san_of(rooks, "a1", "a3"), "R1a3");
+    let queens = "K7/8/8/8/4Q2Q/k7/8/7Q w - - 0 1";
+    assert_eq!(san_of(queens, "h4", "e1"), "Qh4e1");
+}
+
+#[test]
+fn parses_moves() {
+    let start = Position::new();
+    let mv = ChessMove::parse_san(&start, "Nf3").unwrap();
+    assert_eq!(mv.to_string(), "g1f3");
+    assert_eq!(
+        ChessMove::parse_san(&start, "e4!?").unwrap().to_string(),
+        "e2e4"
+    );
+
+    let promotion = Position::from_fen("7k/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let mv = ChessMove::parse_san(&promotion, "e8N").unwrap();
+    assert_eq!(mv.to_string(), "e7e8n");
+    let castling = Position::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert!(
+        ChessMove::parse_san(&castling, "0-0-0")
+            .unwrap()
+            .is_castling()
+    );
+}
+
+#[test]
+fn rejects_bad_moves() {
+    let start = Position::new();
+    assert_eq!(
+        ChessMove::parse_san(&start, "Ke2"),
+        Err(SanError::IllegalMove("Ke2".to_string()))
+    );
+    assert_eq!(
+        ChessMove::parse_san(&start, "hello"),
+        Err(SanError::InvalidSyntax("hello".to_string()))
+    );
+    let knights =
+        Position::from_fen("rnbqkb1r/ppp2ppp/5n2/3pp3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 0 3")
+            .unwrap();
+    assert_eq!(
+        ChessMove::parse_san(&knights, "Nd7"),
+        Err(SanError::AmbiguousMove("Nd7".to_string()))
+    );
+    let promotion = Position::from_fen("7k/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(ChessMove::parse_san(&promotion, "e8").is_err());
+}
+
+#[test]
+fn every_legal_move_round_trips() {
+    for fen in [
+        Position::START_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "K7/8/8/8/4Q2Q/k7/8/7Q w - - 0 1",
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        for mv in position.generate_moves() {
+            let san = mv.to_san(&position);
+            assert_eq!(ChessMove::parse_san(&position, &san), Ok(mv), "{}", san);
+        }
+    }
+}
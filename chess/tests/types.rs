@@ -0,0 +1,74 @@
+use chess::{Bitboard, CastlingRights, Color, MoveKind, Piece, PieceKind, Square};
+
+#[test]
+fn squares_round_trip_through_algebraic_names() {
+    for index in 0..64 {
+        let square = Square::from_index(index);
+        assert_eq!(Square::from_algebraic(&square.to_string()), Some(square));
+    }
+    let e4 = Square::from_algebraic("e4").unwrap();
+    assert_eq!((e4.file(), e4.rank()), (4, 3));
+    assert_eq!(Square::from_algebraic("i1"), None);
+    assert_eq!(Square::from_algebraic("a9"), None);
+    assert_eq!(Square::from_algebraic("e"), None);
+}
+
+#[test]
+fn squares_use_little_endian_rank_file_order() {
+    let index = |name| Square::from_algebraic(name).unwrap().index();
+    assert_eq!(index("a1"), 0);
+    assert_eq!(index("h1"), 7);
+    assert_eq!(index("e1"), 4);
+    assert_eq!(index("a2"), 8);
+    assert_eq!(index("h8"), 63);
+    let a1 = Square::from_algebraic("a1").unwrap();
+    let h1 = Square::from_algebraic("h1").unwrap();
+    assert!(Bitboard::DARK_SQUARES.contains(a1));
+    assert!(!Bitboard::DARK_SQUARES.contains(h1));
+    assert!(!Bitboard::NOT_A_FILE.contains(a1));
+    assert!(!Bitboard::NOT_H_FILE.contains(h1));
+}
+
+#[test]
+fn bitboards_iterate_over_their_squares() {
+    let names = ["a1", "e4", "h8"];
+    let bitboard = names.iter().fold(Bitboard::EMPTY, |acc, name| {
+        acc | Square::from_algebraic(name).unwrap().bitboard()
+    });
+    assert_eq!(bitboard.count(), 3);
+    let mut found: Vec<String> = bitboard.iter().map(|square| square.to_string()).collect();
+    found.sort();
+    assert_eq!(found, names);
+    // iterating leaves the bitboard itself alone
+    assert_eq!(bitboard.iter().len(), 3);
+    assert_eq!(bitboard.count(), 3);
+    assert_eq!(Bitboard::EMPTY.first(), None);
+}
+
+#[test]
+fn pieces_index_and_letters() {
+    for (index, piece) in Piece::ALL.into_iter().enumerate() {
+        assert_eq!(piece.index(), index);
+        assert_eq!(Piece::from_index(index), piece);
+        assert_eq!(Piece::from_char(piece.to_char()), Some(piece));
+    }
+    let black_knight = Piece::new(Color::Black, PieceKind::Knight);
+    assert_eq!(black_knight.to_string(), "n");
+    assert_eq!(Piece::from_char('x'), None);
+    assert_eq!(!Color::White, Color::Black);
+}
+
+#[test]
+fn castling_rights_follow_fen_order() {
+    let position = chess::Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1").unwrap();
+    let rights = position.castling_rights();
+    assert!(rights.contains(CastlingRights::WHITE_KINGSIDE | CastlingRights::BLACK_QUEENSIDE));
+    assert!(!rights.contains(CastlingRights::WHITE_QUEENSIDE));
+    assert_eq!(rights.indices().collect::<Vec<_>>(), [0, 3]);
+    let castle = position
+        .generate_moves()
+        .into_iter()
+        .find(|mv| mv.is_castling())
+        .unwrap();
+    assert_eq!(castle.kind, MoveKind::KingsideCastle);
+}
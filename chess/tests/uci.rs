@@ -0,0 +1,60 @@
+use chess::{ChessMove, Position, UciError};
+
+#[test]
+fn castling_reads_in_both_encodings() {
+    let position = Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    for (two_squares, takes_rook) in [("e1g1", "e1h1"), ("e1c1", "e1a1")] {
+        let mv = ChessMove::parse_uci(&position, two_squares).unwrap();
+        assert!(mv.is_castling());
+        assert_eq!(ChessMove::parse_uci(&position, takes_rook), Ok(mv));
+        assert_eq!(mv.to_uci(false), two_squares);
+        assert_eq!(mv.to_uci(true), takes_rook);
+    }
+}
+
+#[test]
+fn promotions_need_their_piece() {
+    let position = Position::from_fen("7k/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let mv = ChessMove::parse_uci(&position, "e7e8n").unwrap();
+    assert_eq!(mv.to_uci(false), "e7e8n");
+    assert_eq!(
+        ChessMove::parse_uci(&position, "e7e8"),
+        Err(UciError::IllegalMove("e7e8".to_string()))
+    );
+    assert_eq!(
+        ChessMove::parse_uci(&position, "e7e8k"),
+        Err(UciError::InvalidSyntax("e7e8k".to_string()))
+    );
+}
+
+#[test]
+fn rejects_bad_moves() {
+    let start = Position::new();
+    assert_eq!(
+        ChessMove::parse_uci(&start, "e2e5"),
+        Err(UciError::IllegalMove("e2e5".to_string()))
+    );
+    for text in ["e9e4", "e2", "0000", "e2e4qq"] {
+        assert_eq!(
+            ChessMove::parse_uci(&start, text),
+            Err(UciError::InvalidSyntax(text.to_string()))
+        );
+    }
+}
+
+#[test]
+fn every_legal_move_round_trips() {
+    for fen in [
+        Position::START_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        for mv in position.generate_moves() {
+            for king_takes_rook in [false, true] {
+                let uci = mv.to_uci(king_takes_rook);
+                assert_eq!(ChessMove::parse_uci(&position, &uci), Ok(mv), "{}", uci);
+            }
+        }
+    }
+}